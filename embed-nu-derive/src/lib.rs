@@ -0,0 +1,291 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Ident, Lit, LitChar, LitStr, Meta,
+    parse_macro_input, spanned::Spanned,
+};
+
+/// Derives `NuCommandArgs` for a struct with named fields.
+/// The signature of the command is generated from the fields and
+/// the doc comments are used as descriptions.
+///
+/// Struct attributes:
+/// - `#[nu(name = "deploy")]` the name of the command (required)
+/// - `#[nu(category = "custom")]` the category of the command
+///
+/// Field attributes:
+/// - no attribute: a positional parameter. `Option` fields are optional
+///   unless a required positional parameter follows them
+/// - `#[nu(flag)]` a named flag. `bool` fields become switches and
+///   flags of other types are required unless they are an `Option`
+/// - `#[nu(short = 'f')]` the short name of a flag
+/// - `#[nu(rest)]` the remaining positional arguments collected into a `Vec`
+#[proc_macro_derive(NuCommand, attributes(nu))]
+pub fn derive_nu_command(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match derive(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+enum FieldKind {
+    Positional,
+    Switch(Option<LitChar>),
+    Named(Option<LitChar>),
+    Rest,
+}
+
+fn derive(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let mut name = None;
+    let mut category = LitStr::new("default", ident.span());
+
+    for attr in nu_attrs(&input.attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.path.is_ident("category") {
+                category = meta.value()?.parse::<LitStr>()?;
+            } else {
+                return Err(meta.error("unknown nu attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    let name = name.ok_or_else(|| {
+        syn::Error::new(ident.span(), "missing command name: #[nu(name = \"...\")]")
+    })?;
+    let description = doc_string(&input.attrs);
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "NuCommand can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            data.fields.span(),
+            "NuCommand can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut signature = Vec::new();
+    let mut parse = Vec::new();
+    let mut field_names = Vec::new();
+    let mut positional_types = Vec::new();
+    let mut position = 0usize;
+    let mut has_rest = false;
+
+    for field in &fields.named {
+        let field_ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let field_description = doc_string(&field.attrs);
+        let kind = field_kind(field_ident, &field.attrs, ty)?;
+        field_names.push(field_ident);
+
+        if has_rest && matches!(kind, FieldKind::Positional | FieldKind::Rest) {
+            return Err(syn::Error::new(
+                field_ident.span(),
+                "positional parameters must be declared before the rest parameter",
+            ));
+        }
+
+        match kind {
+            FieldKind::Positional => {
+                let param_name = field_ident.to_string();
+                positional_types.push(ty);
+                signature.push(quote! {
+                    let (shape, optional) = positionals.next().expect("positional parameter");
+                    let signature = if optional {
+                        signature.optional(#param_name, shape, #field_description)
+                    } else {
+                        signature.required(#param_name, shape, #field_description)
+                    };
+                });
+                parse.push(quote! {
+                    let #field_ident = <#ty as ::embed_nu::nu_protocol::FromValue>::from_value(
+                        call.opt::<::embed_nu::Value>(engine_state, stack, #position)?
+                            .unwrap_or_else(|| ::embed_nu::Value::nothing(call.head)),
+                    )?;
+                });
+                position += 1;
+            }
+            FieldKind::Switch(short) => {
+                let flag_name = flag_name(field_ident);
+                let short = option_tokens(short);
+                signature.push(quote! {
+                    let signature = signature.switch(#flag_name, #field_description, #short);
+                });
+                parse.push(quote! {
+                    let #field_ident = call.has_flag(engine_state, stack, #flag_name)?;
+                });
+            }
+            FieldKind::Named(short) => {
+                let flag_name = flag_name(field_ident);
+                let short = option_tokens(short);
+                signature.push(quote! {
+                    let signature = if ::embed_nu::accepts_nothing::<#ty>() {
+                        signature.named(
+                            #flag_name,
+                            ::embed_nu::parameter_shape::<#ty>(),
+                            #field_description,
+                            #short,
+                        )
+                    } else {
+                        signature.required_named(
+                            #flag_name,
+                            ::embed_nu::parameter_shape::<#ty>(),
+                            #field_description,
+                            #short,
+                        )
+                    };
+                });
+                parse.push(quote! {
+                    let #field_ident = <#ty as ::embed_nu::nu_protocol::FromValue>::from_value(
+                        call.get_flag::<::embed_nu::Value>(engine_state, stack, #flag_name)?
+                            .unwrap_or_else(|| ::embed_nu::Value::nothing(call.head)),
+                    )?;
+                });
+            }
+            FieldKind::Rest => {
+                let param_name = field_ident.to_string();
+                has_rest = true;
+                signature.push(quote! {
+                    let signature = signature.rest(
+                        #param_name,
+                        ::embed_nu::rest_shape(::embed_nu::parameter_shape::<#ty>()),
+                        #field_description,
+                    );
+                });
+                parse.push(quote! {
+                    let #field_ident = <#ty as ::embed_nu::nu_protocol::FromValue>::from_value(
+                        ::embed_nu::Value::list(
+                            call.rest::<::embed_nu::Value>(engine_state, stack, #position)?,
+                            call.head,
+                        ),
+                    )?;
+                });
+            }
+        }
+    }
+
+    Ok(quote! {
+        impl ::embed_nu::NuCommandArgs for #ident {
+            fn name() -> &'static str {
+                #name
+            }
+
+            fn description() -> &'static str {
+                #description
+            }
+
+            fn signature() -> ::embed_nu::nu_protocol::Signature {
+                let signature = ::embed_nu::nu_protocol::Signature::build(#name)
+                    .input_output_types(vec![(
+                        ::embed_nu::nu_protocol::Type::Any,
+                        ::embed_nu::nu_protocol::Type::Any,
+                    )])
+                    .category(::embed_nu::command_category(#category));
+                #[allow(unused_mut, unused_variables)]
+                let mut positionals = ::embed_nu::positional_parameters(vec![#((
+                    ::embed_nu::parameter_shape::<#positional_types>(),
+                    ::embed_nu::accepts_nothing::<#positional_types>(),
+                )),*])
+                .into_iter();
+                #(#signature)*
+
+                signature
+            }
+
+            fn from_call(
+                engine_state: &::embed_nu::nu_protocol::engine::EngineState,
+                stack: &mut ::embed_nu::nu_protocol::engine::Stack,
+                call: &::embed_nu::nu_protocol::engine::Call,
+            ) -> Result<Self, ::embed_nu::nu_protocol::ShellError> {
+                use ::embed_nu::CallExt;
+                #(#parse)*
+
+                Ok(Self { #(#field_names),* })
+            }
+        }
+    })
+}
+
+fn field_kind(ident: &Ident, attrs: &[Attribute], ty: &syn::Type) -> syn::Result<FieldKind> {
+    let mut flag = false;
+    let mut rest = false;
+    let mut short = None;
+
+    for attr in nu_attrs(attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("flag") {
+                flag = true;
+            } else if meta.path.is_ident("rest") {
+                rest = true;
+            } else if meta.path.is_ident("short") {
+                short = Some(meta.value()?.parse::<LitChar>()?);
+            } else {
+                return Err(meta.error("unknown nu attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    if rest && (flag || short.is_some()) {
+        return Err(syn::Error::new(
+            ident.span(),
+            "a rest parameter can't be a flag",
+        ));
+    }
+
+    if rest {
+        Ok(FieldKind::Rest)
+    } else if flag || short.is_some() {
+        if is_bool(ty) {
+            Ok(FieldKind::Switch(short))
+        } else {
+            Ok(FieldKind::Named(short))
+        }
+    } else {
+        Ok(FieldKind::Positional)
+    }
+}
+
+fn nu_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("nu"))
+}
+
+fn doc_string(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(doc), ..
+                }) => Some(doc.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn flag_name(ident: &Ident) -> String {
+    ident.to_string().replace('_', "-")
+}
+
+fn option_tokens(short: Option<LitChar>) -> TokenStream2 {
+    match short {
+        Some(short) => quote!(Some(#short)),
+        None => quote!(None),
+    }
+}
+
+fn is_bool(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(path) if path.path.is_ident("bool"))
+}
@@ -1,6 +1,9 @@
-use nu_protocol::{ast::Expression, Span, Spanned};
+use nu_protocol::{Span, Spanned, ast::Expression};
 
-use crate::{into_expression::IntoExpression, NewEmpty};
+use crate::{
+    NewEmpty,
+    into_expression::{Captures, IntoExpression},
+};
 
 /// A struct representing the argument to a function
 pub enum Argument {
@@ -39,6 +42,14 @@ impl Argument {
 /// Converts a given type into an argument
 pub trait IntoArgument {
     fn into_argument(self) -> Argument;
+
+    /// Converts into an argument together with the values captured by closures passed in it
+    fn into_argument_with_captures(self) -> (Argument, Captures)
+    where
+        Self: Sized,
+    {
+        (self.into_argument(), Vec::new())
+    }
 }
 
 impl<E: IntoExpression> IntoArgument for E {
@@ -46,6 +57,11 @@ impl<E: IntoExpression> IntoArgument for E {
     fn into_argument(self) -> Argument {
         Argument::positional(self)
     }
+
+    fn into_argument_with_captures(self) -> (Argument, Captures) {
+        let (expression, captures) = self.into_expression_with_captures();
+        (Argument::Positional(expression), captures)
+    }
 }
 
 impl IntoArgument for Argument {
@@ -54,3 +70,20 @@ impl IntoArgument for Argument {
         self
     }
 }
+
+/// An argument that has already been converted, keeping the values captured by its closures
+#[cfg(feature = "async")]
+pub(crate) struct CapturedArgument(pub Argument, pub Captures);
+
+#[cfg(feature = "async")]
+impl IntoArgument for CapturedArgument {
+    #[inline]
+    fn into_argument(self) -> Argument {
+        self.0
+    }
+
+    #[inline]
+    fn into_argument_with_captures(self) -> (Argument, Captures) {
+        (self.0, self.1)
+    }
+}
@@ -0,0 +1,127 @@
+use std::{any::Any, fmt, sync::Arc};
+
+use nu_protocol::{
+    CustomValue, PipelineData, ShellError, Span, Type, Value, VarId,
+    ast::Expr,
+    engine::{EngineState, Stack, StateWorkingSet},
+};
+
+use crate::{
+    commands::{CALLBACK_COMMAND, CallbackCommand},
+    error::{CrateError, CrateResult},
+    utils::{NewEmpty, impl_unserializable},
+};
+
+/// A rust function that can be called by scripts like a closure.
+/// It receives the positional arguments and the pipeline input of the call
+pub type CallbackFn =
+    dyn Fn(Vec<Value>, PipelineData) -> Result<PipelineData, ShellError> + Send + Sync;
+
+/// The opaque value captured by the closure of a callback.
+/// Scripts can't create it, so a callback can only be called through its closure.
+/// The callback is freed when the last closure capturing it is dropped
+#[derive(Clone)]
+pub(crate) struct CallbackHandle {
+    pub func: Arc<CallbackFn>,
+    pub first: Option<VarId>,
+    pub rest: Option<VarId>,
+}
+
+impl fmt::Debug for CallbackHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CallbackHandle")
+    }
+}
+
+impl_unserializable!(CallbackHandle);
+
+#[typetag::serde]
+impl CustomValue for CallbackHandle {
+    fn clone_value(&self, span: Span) -> Value {
+        Value::custom(Box::new(self.clone()), span)
+    }
+
+    fn type_name(&self) -> String {
+        String::from("callback")
+    }
+
+    fn to_base_value(&self, span: Span) -> Result<Value, ShellError> {
+        Ok(Value::string("<callback>", span))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Creates a closure value that calls the given callback.
+/// The callback command called by the closure is only registered once per engine state
+pub(crate) fn create_callback(
+    engine_state: &mut EngineState,
+    stack: &mut Stack,
+    callback: Arc<CallbackFn>,
+) -> CrateResult<Value> {
+    if engine_state
+        .find_decl(CALLBACK_COMMAND.as_bytes(), &[])
+        .is_none()
+    {
+        let mut working_set = StateWorkingSet::new(engine_state);
+        working_set.add_decl(Box::new(CallbackCommand));
+        let delta = working_set.render();
+        engine_state.merge_delta(delta)?;
+    }
+    let mut working_set = StateWorkingSet::new(engine_state);
+
+    // the variable holding the handle is only in scope while the closure is parsed
+    working_set.enter_scope();
+    let handle_id = working_set.add_variable(
+        b"callback".to_vec(),
+        Span::empty(),
+        Type::Custom("callback".into()),
+        false,
+    );
+    // commands like `each` only bind the parameters they pass values to,
+    // so the callback command reads the parameters from the stack itself
+    let contents = format!("{{|first?, ...rest| {CALLBACK_COMMAND} $callback }}");
+    // the parser returns the cached block for a file it has seen before,
+    // so every callback gets a file of its own
+    let fname = format!("embed-nu-callback-{}", handle_id.get());
+    let block = nu_parser::parse(&mut working_set, Some(&fname), contents.as_bytes(), false);
+    working_set.exit_scope();
+
+    if !working_set.parse_errors.is_empty() {
+        return Err(CrateError::NuParseErrors(working_set.parse_errors));
+    }
+    let Some(Expr::Closure(closure_id)) = block
+        .pipelines
+        .first()
+        .and_then(|p| p.elements.first())
+        .map(|e| &e.expr.expr)
+    else {
+        unreachable!("the callback script is a single closure");
+    };
+    let signature = &working_set.get_block(*closure_id).signature;
+    let handle = CallbackHandle {
+        func: callback,
+        first: signature.optional_positional.first().and_then(|p| p.var_id),
+        rest: signature.rest_positional.as_ref().and_then(|p| p.var_id),
+    };
+    let delta = working_set.render();
+    engine_state.merge_delta(delta)?;
+
+    stack.add_var(handle_id, Value::custom(Box::new(handle), Span::empty()));
+    let closure = nu_engine::eval_block::<nu_protocol::debugger::WithoutDebug>(
+        engine_state,
+        stack,
+        &block,
+        PipelineData::empty(),
+    )
+    .and_then(|data| data.into_value(Span::empty()));
+    stack.remove_var(handle_id);
+
+    Ok(closure?)
+}
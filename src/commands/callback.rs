@@ -0,0 +1,74 @@
+use nu_engine::CallExt;
+use nu_protocol::engine::{Call, Command, EngineState, Stack};
+use nu_protocol::{Category, PipelineData, ShellError, Signature, SyntaxShape, Type, Value};
+
+use crate::callback::CallbackHandle;
+
+/// Name of the command called by the closures created for host callbacks
+pub(crate) const CALLBACK_COMMAND: &str = "embed-nu callback";
+
+/// Command that is called by the closures created for host callbacks.
+/// The closure passes the handle of its callback, the arguments are read
+/// from the parameters of the closure
+#[derive(Clone)]
+pub(crate) struct CallbackCommand;
+
+impl Command for CallbackCommand {
+    fn name(&self) -> &str {
+        CALLBACK_COMMAND
+    }
+
+    fn description(&self) -> &str {
+        "Calls a callback provided by the host application"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(CALLBACK_COMMAND)
+            .input_output_types(vec![(Type::Any, Type::Any)])
+            .required("handle", SyntaxShape::Any, "the handle of the callback")
+            .category(Category::Custom(String::from("host")))
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        engine_state.signals().check(call.head)?;
+        let handle: Value = call.req(engine_state, stack, 0)?;
+        let callback = match &handle {
+            Value::Custom { val, .. } => val.as_any().downcast_ref::<CallbackHandle>(),
+            _ => None,
+        }
+        .ok_or_else(|| ShellError::GenericError {
+            error: String::from("Not a callback"),
+            msg: String::from("callbacks can only be called through their closures"),
+            span: Some(call.head),
+            help: None,
+            inner: Vec::new(),
+        })?;
+
+        // parameters that weren't passed to the closure aren't bound at all
+        let first = callback
+            .first
+            .and_then(|var_id| stack.get_var(var_id, call.head).ok());
+        let rest = match callback
+            .rest
+            .and_then(|var_id| stack.get_var(var_id, call.head).ok())
+        {
+            Some(Value::List { vals, .. }) => vals,
+            _ => Vec::new(),
+        };
+        // `do` binds a missing first parameter to null, which
+        // is only an argument if there are more arguments after it
+        let mut args = Vec::new();
+        if let Some(first) = first.filter(|first| !first.is_nothing() || !rest.is_empty()) {
+            args.push(first);
+        }
+        args.extend(rest);
+
+        (callback.func)(args, input)
+    }
+}
@@ -0,0 +1,53 @@
+use std::marker::PhantomData;
+
+use nu_protocol::engine::{Call, Command, EngineState, Stack};
+use nu_protocol::{PipelineData, ShellError, Signature};
+
+use crate::struct_command::{CommandContext, NuCommand};
+
+/// Adapter to register a [NuCommand] struct as nu command
+pub(crate) struct DerivedCommand<T>(PhantomData<fn() -> T>);
+
+impl<T> DerivedCommand<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Clone for DerivedCommand<T> {
+    fn clone(&self) -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: NuCommand> Command for DerivedCommand<T> {
+    fn name(&self) -> &str {
+        T::name()
+    }
+
+    fn description(&self) -> &str {
+        T::description()
+    }
+
+    fn signature(&self) -> Signature {
+        T::signature()
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        engine_state.signals().check(call.head)?;
+        let command = T::from_call(engine_state, stack, call)?;
+        let ctx = CommandContext {
+            engine_state,
+            stack,
+            head: call.head,
+        };
+
+        command.run(ctx, input)
+    }
+}
@@ -0,0 +1,103 @@
+use nu_engine::CallExt;
+use nu_protocol::engine::{Call, Closure, Command, EngineState, Stack};
+use nu_protocol::{Category, PipelineData, ShellError, Signature, SyntaxShape, Type, Value};
+
+use crate::events::{Event, EventBus};
+
+/// Emits an event to the host
+#[derive(Clone)]
+pub(crate) struct EmitCommand {
+    events: EventBus,
+}
+
+impl EmitCommand {
+    pub fn new(events: EventBus) -> Self {
+        Self { events }
+    }
+}
+
+impl Command for EmitCommand {
+    fn name(&self) -> &str {
+        "emit"
+    }
+
+    fn description(&self) -> &str {
+        "Emits an event to the host application. \
+        The payload is taken from the input if it isn't passed as argument."
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("emit")
+            .input_output_types(vec![(Type::Any, Type::Nothing)])
+            .required("event", SyntaxShape::String, "the name of the event")
+            .optional("payload", SyntaxShape::Any, "the payload of the event")
+            .category(Category::Custom(String::from("host")))
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        engine_state.signals().check(call.head)?;
+        let name: String = call.req(engine_state, stack, 0)?;
+        let payload = match call.opt::<Value>(engine_state, stack, 1)? {
+            Some(payload) => payload,
+            None => input.into_value(call.head)?,
+        };
+        self.events.emit(Event { name, payload });
+
+        Ok(PipelineData::empty())
+    }
+}
+
+/// Registers a closure that is called when the host triggers the event
+#[derive(Clone)]
+pub(crate) struct OnCommand {
+    events: EventBus,
+}
+
+impl OnCommand {
+    pub fn new(events: EventBus) -> Self {
+        Self { events }
+    }
+}
+
+impl Command for OnCommand {
+    fn name(&self) -> &str {
+        "on"
+    }
+
+    fn description(&self) -> &str {
+        "Registers a handler for an event triggered by the host application."
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("on")
+            .input_output_types(vec![(Type::Nothing, Type::Nothing)])
+            .required("event", SyntaxShape::String, "the name of the event")
+            .required(
+                "handler",
+                SyntaxShape::Closure(Some(vec![SyntaxShape::Any])),
+                "the closure called with the payload of the event",
+            )
+            .category(Category::Custom(String::from("host")))
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        engine_state.signals().check(call.head)?;
+        let name: String = call.req(engine_state, stack, 0)?;
+        let handler: Closure = call.req(engine_state, stack, 1)?;
+        self.events.add_handler(name, handler);
+
+        Ok(PipelineData::empty())
+    }
+}
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use nu_engine::CallExt;
+use nu_protocol::engine::{Call, Command, EngineState, Stack};
+use nu_protocol::{Category, PipelineData, ShellError, Signature, SyntaxShape, Type, Value};
+
+use crate::external::{ExternalAction, ExternalCall, ExternalHandler};
+
+/// Replacement for `run-external` that passes every external call
+/// to the host handler before anything gets spawned
+#[derive(Clone)]
+pub(crate) struct ExternalInterceptor {
+    handler: Arc<dyn ExternalHandler>,
+}
+
+impl ExternalInterceptor {
+    pub fn new(handler: Arc<dyn ExternalHandler>) -> Self {
+        Self { handler }
+    }
+}
+
+impl Command for ExternalInterceptor {
+    fn name(&self) -> &str {
+        "run-external"
+    }
+
+    fn description(&self) -> &str {
+        "Runs external commands after they have been approved by the host."
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name())
+            .input_output_types(vec![(Type::Any, Type::Any)])
+            .required(
+                "command",
+                SyntaxShape::OneOf(vec![SyntaxShape::GlobPattern, SyntaxShape::String]),
+                "External command to run.",
+            )
+            .rest(
+                "args",
+                SyntaxShape::OneOf(vec![SyntaxShape::GlobPattern, SyntaxShape::Any]),
+                "Arguments for external command.",
+            )
+            .category(Category::System)
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        engine_state.signals().check(call.head)?;
+        let program = call
+            .req::<Value>(engine_state, stack, 0)?
+            .coerce_into_string()?;
+        let args = call.rest::<Value>(engine_state, stack, 1)?;
+        let external = ExternalCall {
+            program,
+            args,
+            env: stack.get_env_vars(engine_state),
+            input,
+            span: call.head,
+        };
+
+        match self.handler.handle(external) {
+            ExternalAction::Run(external) => run_external(engine_state, stack, call, external),
+            ExternalAction::Respond(data) => Ok(data),
+            ExternalAction::Deny(reason) => Err(ShellError::GenericError {
+                error: String::from("External command denied"),
+                msg: reason,
+                span: Some(call.head),
+                help: None,
+                inner: Vec::new(),
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "system")]
+fn run_external(
+    engine_state: &EngineState,
+    stack: &Stack,
+    call: &Call,
+    external: ExternalCall,
+) -> Result<PipelineData, ShellError> {
+    use std::collections::HashMap;
+
+    use crate::ValueIntoExpression;
+
+    // the arguments keep their shape, so globs are still expanded
+    let arguments = std::iter::once(Value::string(external.program, external.span))
+        .chain(external.args)
+        .map(|arg| nu_protocol::ast::Argument::Positional(arg.into_expression()))
+        .collect();
+    let ast_call = nu_protocol::ast::Call {
+        decl_id: call.decl_id,
+        head: external.span,
+        arguments,
+        parser_info: HashMap::new(),
+    };
+
+    // the handler may have changed the environment of the call
+    let mut stack = stack.clone();
+    for name in stack.get_env_var_names(engine_state) {
+        if !external.env.contains_key(&name) {
+            stack.remove_env_var(engine_state, &name);
+        }
+    }
+    for (name, value) in external.env {
+        stack.add_env_var(name, value);
+    }
+
+    nu_command::External.run(
+        engine_state,
+        &mut stack,
+        &(&ast_call).into(),
+        external.input,
+    )
+}
+
+#[cfg(not(feature = "system"))]
+fn run_external(
+    _engine_state: &EngineState,
+    _stack: &Stack,
+    call: &Call,
+    _external: ExternalCall,
+) -> Result<PipelineData, ShellError> {
+    Err(ShellError::GenericError {
+        error: String::from("External commands are not available"),
+        msg: String::from("embed-nu was built without the `system` feature"),
+        span: Some(call.head),
+        help: None,
+        inner: Vec::new(),
+    })
+}
@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use nu_engine::CallExt;
+use nu_protocol::engine::{Call, Command, EngineState, Stack};
+use nu_protocol::{Category, PipelineData, ShellError, Signature, Span, Type, Value};
+
+use crate::host_fn::HostFn;
+
+type BoxedHostFn = dyn Fn(Vec<Option<Value>>, Span) -> Result<Value, ShellError> + Send + Sync;
+
+/// A command that calls a rust closure with the
+/// positional arguments converted to the closures parameter types
+#[derive(Clone)]
+pub(crate) struct FnCommand {
+    signature: Signature,
+    func: Arc<BoxedHostFn>,
+}
+
+impl FnCommand {
+    pub fn new<Args, F: HostFn<Args>>(name: String, func: F) -> Self {
+        let mut signature = Signature::build(name)
+            .input_output_types(vec![(Type::Any, Type::Any)])
+            .category(Category::Custom(String::from("host")));
+
+        for (i, (shape, optional)) in F::parameters().into_iter().enumerate() {
+            let param_name = format!("arg{i}");
+            signature = if optional {
+                signature.optional(param_name, shape, "")
+            } else {
+                signature.required(param_name, shape, "")
+            };
+        }
+
+        Self {
+            signature,
+            func: Arc::new(move |args, span| func.call(args, span)),
+        }
+    }
+}
+
+impl Command for FnCommand {
+    fn name(&self) -> &str {
+        &self.signature.name
+    }
+
+    fn description(&self) -> &str {
+        "A function provided by the host application"
+    }
+
+    fn signature(&self) -> Signature {
+        self.signature.clone()
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        // the evaluator doesn't check for interrupts between calls,
+        // so loops only calling host functions would never stop otherwise
+        engine_state.signals().check(call.head)?;
+        let num_params =
+            self.signature.required_positional.len() + self.signature.optional_positional.len();
+        let args = (0..num_params)
+            .map(|i| call.opt::<Value>(engine_state, stack, i))
+            .collect::<Result<Vec<_>, _>>()?;
+        let value = (self.func)(args, call.head)?;
+
+        Ok(PipelineData::Value(value.with_span(call.head), None))
+    }
+}
@@ -0,0 +1,263 @@
+use std::sync::Arc;
+
+use nu_engine::CallExt;
+use nu_protocol::ast::CellPath;
+use nu_protocol::engine::{Call, Command, EngineState, Stack};
+use nu_protocol::{Category, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value};
+
+use crate::interaction::{
+    ConfirmPrompt, InteractionProvider, PromptResponse, SelectPrompt, TextPrompt,
+};
+
+/// Replacement for `input` that asks the host instead of reading from stdin.
+/// Wrapped in an `OverrideCommand` to keep the signature of the builtin
+#[derive(Clone)]
+pub(crate) struct InputCommand {
+    provider: Arc<dyn InteractionProvider>,
+}
+
+impl InputCommand {
+    pub fn new(provider: Arc<dyn InteractionProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+impl Command for InputCommand {
+    fn name(&self) -> &str {
+        "input"
+    }
+
+    fn description(&self) -> &str {
+        "Asks the host application for user input."
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name()).category(Category::Platform)
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        engine_state.signals().check(call.head)?;
+        let message: Option<String> = call.opt(engine_state, stack, 0)?;
+        let until: Option<String> = call.get_flag(engine_state, stack, "bytes-until-any")?;
+        let numchar: Option<usize> = call.get_flag(engine_state, stack, "numchar")?;
+        let default: Option<String> = call.get_flag(engine_state, stack, "default")?;
+        let prompt = TextPrompt {
+            message: message.unwrap_or_default(),
+            default: default.clone(),
+            secret: call.has_flag(engine_state, stack, "suppress-output")?,
+        };
+
+        let mut text = match self.provider.input(prompt) {
+            PromptResponse::Value(text) => text,
+            PromptResponse::Cancelled => return Err(cancelled(call.head)),
+            PromptResponse::Unavailable(reason) => return Err(unavailable(reason, call.head)),
+        };
+        if let Some(end) = until.and_then(|stops| text.find(|c| stops.contains(c))) {
+            text.truncate(end);
+        }
+        if let Some(numchar) = numchar {
+            text = text.chars().take(numchar).collect();
+        }
+        if let Some(default) = default.filter(|_| text.is_empty()) {
+            text = default;
+        }
+
+        Ok(PipelineData::Value(Value::string(text, call.head), None))
+    }
+}
+
+/// Replacement for `input list` that lets the host show the selection.
+/// Wrapped in an `OverrideCommand` to keep the signature of the builtin
+#[derive(Clone)]
+pub(crate) struct InputListCommand {
+    provider: Arc<dyn InteractionProvider>,
+}
+
+impl InputListCommand {
+    pub fn new(provider: Arc<dyn InteractionProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+impl Command for InputListCommand {
+    fn name(&self) -> &str {
+        "input list"
+    }
+
+    fn description(&self) -> &str {
+        "Asks the host application to select items from a list."
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name()).category(Category::Platform)
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        engine_state.signals().check(call.head)?;
+        let head = call.head;
+        let message: Option<String> = call.opt(engine_state, stack, 0)?;
+        let multiple = call.has_flag(engine_state, stack, "multi")?;
+        let index = call.has_flag(engine_state, stack, "index")?;
+        let display: Option<CellPath> = call.get_flag(engine_state, stack, "display")?;
+        let config = stack.get_config(engine_state);
+
+        let values = input.into_iter().collect::<Vec<_>>();
+        let options = values
+            .iter()
+            .map(|value| {
+                let shown = match &display {
+                    Some(path) => value.clone().follow_cell_path(&path.members, false)?,
+                    None => value.clone(),
+                };
+                Ok(shown.to_expanded_string(", ", &config))
+            })
+            .collect::<Result<Vec<_>, ShellError>>()?;
+        let prompt = SelectPrompt {
+            message: message.unwrap_or_default(),
+            options,
+            multiple,
+        };
+
+        let selected = match self.provider.select(prompt) {
+            PromptResponse::Value(selected) => selected,
+            PromptResponse::Cancelled => {
+                return Ok(PipelineData::Value(Value::nothing(head), None));
+            }
+            PromptResponse::Unavailable(reason) => return Err(unavailable(reason, head)),
+        };
+        let mut selected = selected
+            .into_iter()
+            .map(|i| match values.get(i) {
+                Some(_) if index => Ok(Value::int(i as i64, head)),
+                Some(value) => Ok(value.clone()),
+                None => Err(ShellError::AccessBeyondEnd {
+                    max_idx: values.len().saturating_sub(1),
+                    span: head,
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let value = if multiple {
+            Value::list(selected, head)
+        } else if selected.is_empty() {
+            Value::nothing(head)
+        } else {
+            selected.swap_remove(0)
+        };
+
+        Ok(PipelineData::Value(value, None))
+    }
+}
+
+/// Asks the host application for a confirmation
+#[derive(Clone)]
+pub(crate) struct ConfirmCommand {
+    provider: Arc<dyn InteractionProvider>,
+}
+
+impl ConfirmCommand {
+    pub fn new(provider: Arc<dyn InteractionProvider>) -> Self {
+        Self { provider }
+    }
+}
+
+impl Command for ConfirmCommand {
+    fn name(&self) -> &str {
+        "confirm"
+    }
+
+    fn description(&self) -> &str {
+        "Asks the host application for a yes or no answer. Cancelling the prompt returns false."
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name())
+            .input_output_types(vec![(Type::Nothing, Type::Bool)])
+            .required("prompt", SyntaxShape::String, "The question to confirm.")
+            .category(Category::Platform)
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        engine_state.signals().check(call.head)?;
+        let message: String = call.req(engine_state, stack, 0)?;
+
+        let confirmed = match self.provider.confirm(ConfirmPrompt { message }) {
+            PromptResponse::Value(confirmed) => confirmed,
+            PromptResponse::Cancelled => false,
+            PromptResponse::Unavailable(reason) => return Err(unavailable(reason, call.head)),
+        };
+
+        Ok(PipelineData::Value(Value::bool(confirmed, call.head), None))
+    }
+}
+
+/// Replacement for `input listen` which reads key presses from the terminal directly
+/// and can't be served by an [InteractionProvider]
+#[derive(Clone)]
+pub(crate) struct InputListenCommand;
+
+impl Command for InputListenCommand {
+    fn name(&self) -> &str {
+        "input listen"
+    }
+
+    fn description(&self) -> &str {
+        "Listening to terminal events isn't supported by the host application."
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build(self.name()).category(Category::Platform)
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        _stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        engine_state.signals().check(call.head)?;
+        Err(unavailable(
+            String::from("the host application doesn't forward terminal events"),
+            call.head,
+        ))
+    }
+}
+
+fn cancelled(span: Span) -> ShellError {
+    ShellError::GenericError {
+        error: String::from("Input cancelled"),
+        msg: String::from("the prompt was dismissed"),
+        span: Some(span),
+        help: None,
+        inner: Vec::new(),
+    }
+}
+
+fn unavailable(reason: String, span: Span) -> ShellError {
+    ShellError::GenericError {
+        error: String::from("Interactive input is not available"),
+        msg: reason,
+        span: Some(span),
+        help: None,
+        inner: Vec::new(),
+    }
+}
@@ -0,0 +1,17 @@
+mod callback;
+mod derived_command;
+mod events;
+mod external;
+mod fn_command;
+mod interaction;
+mod override_command;
+mod print;
+
+pub(crate) use callback::{CALLBACK_COMMAND, CallbackCommand};
+pub(crate) use derived_command::DerivedCommand;
+pub(crate) use events::{EmitCommand, OnCommand};
+pub(crate) use external::ExternalInterceptor;
+pub(crate) use fn_command::FnCommand;
+pub(crate) use interaction::{ConfirmCommand, InputCommand, InputListCommand, InputListenCommand};
+pub(crate) use override_command::OverrideCommand;
+pub use print::PrintCommand;
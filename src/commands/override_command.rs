@@ -0,0 +1,53 @@
+use nu_protocol::engine::{Call, Command, EngineState, Stack};
+use nu_protocol::{PipelineData, ShellError, Signature};
+
+/// Wraps a host command to replace a builtin declaration with the same name.
+/// The signature and description of the replaced declaration are kept so
+/// scripts calling the command are parsed the same way as before.
+#[derive(Clone)]
+pub(crate) struct OverrideCommand {
+    signature: Signature,
+    description: String,
+    extra_description: String,
+    inner: Box<dyn Command>,
+}
+
+impl OverrideCommand {
+    pub fn new(original: &dyn Command, inner: Box<dyn Command>) -> Self {
+        Self {
+            signature: original.signature(),
+            description: original.description().to_string(),
+            extra_description: original.extra_description().to_string(),
+            inner,
+        }
+    }
+}
+
+impl Command for OverrideCommand {
+    fn name(&self) -> &str {
+        &self.signature.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn extra_description(&self) -> &str {
+        &self.extra_description
+    }
+
+    fn signature(&self) -> Signature {
+        self.signature.clone()
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        engine_state.signals().check(call.head)?;
+        self.inner.run(engine_state, stack, call, input)
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context as TaskContext, Poll},
+};
+
+use futures_core::Stream;
+use nu_protocol::{PipelineData, ShellError, Signals, Span, Value};
+use tokio::{sync::mpsc, task::JoinError};
+
+use crate::{
+    NewEmpty,
+    argument::{CapturedArgument, IntoArgument},
+    error::{CrateError, CrateResult},
+};
+
+use super::Context;
+
+/// The number of values buffered by a [ValueStream] before the evaluation waits for the consumer
+const STREAM_BUFFER: usize = 32;
+
+impl Context {
+    /// Evals nu script as string on the blocking thread pool of tokio
+    /// and collects the output into a single value.
+    /// Dropping the future interrupts the evaluation at the next command the script calls.
+    /// Loops that never call a command can't be interrupted.
+    /// Definitions and variables of the script are kept when the future completes
+    pub async fn eval_raw_async<S: ToString>(
+        &mut self,
+        contents: S,
+        input: PipelineData,
+    ) -> CrateResult<Value> {
+        let contents = contents.to_string();
+
+        self.run_blocking(move |ctx| {
+            ctx.eval_raw(contents, input)?
+                .into_value(Span::empty())
+                .map_err(CrateError::from)
+        })
+        .await
+    }
+
+    /// Calls a function by the given name on the blocking thread pool of tokio
+    /// and collects the output into a single value.
+    /// Dropping the future interrupts the call at the next command it calls.
+    /// Loops that never call a command can't be interrupted
+    pub async fn call_fn_async<S, I, A>(&mut self, name: S, args: I) -> CrateResult<Value>
+    where
+        S: ToString,
+        I: IntoIterator<Item = A>,
+        A: IntoArgument,
+    {
+        let name = name.to_string();
+        let args = args
+            .into_iter()
+            .map(|a| {
+                let (argument, captures) = a.into_argument_with_captures();
+                CapturedArgument(argument, captures)
+            })
+            .collect::<Vec<_>>();
+
+        self.run_blocking(move |ctx| {
+            ctx.call_fn(name, args)?
+                .into_value(Span::empty())
+                .map_err(CrateError::from)
+        })
+        .await
+    }
+
+    /// Evals nu script as string on the blocking thread pool of tokio
+    /// and returns the output values as they are produced.
+    /// The script runs on a copy of the context so definitions aren't kept.
+    /// Dropping the stream interrupts the evaluation at the next command the script calls.
+    /// Loops that never call a command can't be interrupted
+    pub fn eval_stream_async<S: ToString>(&self, contents: S, input: PipelineData) -> ValueStream {
+        let contents = contents.to_string();
+        let interrupt = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        let mut ctx = self.with_interrupt(Arc::clone(&interrupt));
+
+        tokio::task::spawn_blocking(move || {
+            let values = match ctx.eval_raw(contents, input) {
+                Ok(pipeline) => ctx.iter_as::<Value>(pipeline),
+                Err(e) => {
+                    let _ = tx.blocking_send(Err(e));
+                    return;
+                }
+            };
+            for value in values {
+                if tx.blocking_send(value).is_err() {
+                    break;
+                }
+            }
+        });
+
+        ValueStream {
+            receiver: rx,
+            _interrupt: InterruptOnDrop(interrupt),
+        }
+    }
+
+    /// Runs the function with a copy of the context on the blocking thread pool
+    /// and replaces the context with the copy when it completes
+    async fn run_blocking<T, F>(&mut self, func: F) -> CrateResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Context) -> CrateResult<T> + Send + 'static,
+    {
+        let interrupt = Arc::new(AtomicBool::new(false));
+        let _guard = InterruptOnDrop(Arc::clone(&interrupt));
+        let mut ctx = self.with_interrupt(interrupt);
+
+        let (mut ctx, result) = tokio::task::spawn_blocking(move || {
+            let result = func(&mut ctx);
+            (ctx, result)
+        })
+        .await
+        .map_err(join_error)?;
+        Arc::make_mut(&mut ctx.engine_state).set_signals(self.signals().clone());
+        *self = ctx;
+
+        result
+    }
+
+    /// Returns a copy of the context that is interrupted with the given flag
+    fn with_interrupt(&self, interrupt: Arc<AtomicBool>) -> Context {
+        let mut ctx = self.clone();
+        Arc::make_mut(&mut ctx.engine_state).set_signals(Signals::new(interrupt));
+
+        ctx
+    }
+}
+
+/// An async stream of the values returned by [Context::eval_stream_async].
+/// Errors raised while streaming the output are returned as `Err` items
+pub struct ValueStream {
+    receiver: mpsc::Receiver<CrateResult<Value>>,
+    _interrupt: InterruptOnDrop,
+}
+
+impl Stream for ValueStream {
+    type Item = CrateResult<Value>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Interrupts the evaluation when the future or stream owning it is dropped
+struct InterruptOnDrop(Arc<AtomicBool>);
+
+impl Drop for InterruptOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+fn join_error(error: JoinError) -> CrateError {
+    match error.try_into_panic() {
+        Ok(panic) => std::panic::resume_unwind(panic),
+        Err(_) => CrateError::from(ShellError::Interrupted {
+            span: Span::empty(),
+        }),
+    }
+}
@@ -1,8 +1,38 @@
+#[cfg(feature = "core")]
 use nu_cmd_lang::*;
+// every command group except `bit` binds commands of nu-command
+#[cfg(any(
+    feature = "core",
+    feature = "debug",
+    feature = "filter",
+    feature = "chart",
+    feature = "misc",
+    feature = "path",
+    feature = "system",
+    feature = "string",
+    feature = "byte",
+    feature = "file-system",
+    feature = "platform",
+    feature = "date",
+    feature = "shell",
+    feature = "format",
+    feature = "viewer",
+    feature = "conversion",
+    feature = "environment",
+    feature = "math",
+    feature = "network",
+    feature = "database",
+    feature = "random",
+    feature = "generator",
+    feature = "hash",
+    feature = "experimental"
+))]
 use nu_command::*;
 use nu_protocol::engine::{EngineState, StateWorkingSet};
 
-use crate::{commands::PrintCommand, error::CrateResult};
+#[cfg(feature = "core")]
+use crate::commands::PrintCommand;
+use crate::error::CrateResult;
 
 macro_rules! bind_commands {
     ($engine_state:expr, $( $command:expr),* $(,)? ) => {
@@ -12,6 +42,7 @@ macro_rules! bind_commands {
     };
 }
 
+#[cfg(feature = "core")]
 pub fn bind_core_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands!(
         engine_state,
@@ -28,8 +59,10 @@ pub fn bind_core_commands(engine_state: &mut EngineState) -> CrateResult<()> {
         ErrorMake,
         ExportAlias,
         ExportCommand,
+        ExportConst,
         ExportDef,
         ExportExtern,
+        ExportModule,
         ExportUse,
         Extern,
         For,
@@ -39,6 +72,7 @@ pub fn bind_core_commands(engine_state: &mut EngineState) -> CrateResult<()> {
         HelpModules,
         HelpExterns,
         HelpOperators,
+        HelpEscapes,
         Hide,
         HideEnv,
         If,
@@ -55,6 +89,13 @@ pub fn bind_core_commands(engine_state: &mut EngineState) -> CrateResult<()> {
         Module,
         Mut,
         Return,
+        Scope,
+        ScopeAliases,
+        ScopeCommands,
+        ScopeEngineStats,
+        ScopeExterns,
+        ScopeModules,
+        ScopeVariables,
         Try,
         Use,
         Version,
@@ -62,33 +103,46 @@ pub fn bind_core_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     )
 }
 
+#[cfg(feature = "debug")]
 pub fn bind_debug_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands!(
         engine_state,
         Ast,
         Debug,
         DebugInfo,
+        DebugProfile,
         Explain,
         Inspect,
         Metadata,
+        MetadataAccess,
+        MetadataSet,
         TimeIt,
         View,
+        ViewBlocks,
         ViewFiles,
+        ViewIr,
         ViewSource,
         ViewSpan,
     )
 }
 
+#[cfg(feature = "chart")]
 pub fn bind_chart_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands!(engine_state, Histogram)
 }
 
+#[cfg(feature = "filter")]
 pub fn bind_filter_commands(engine_state: &mut EngineState) -> CrateResult<()> {
+    // nu-command only provides shuffle with its rand feature
+    #[cfg(feature = "random")]
+    bind_commands!(engine_state, Shuffle)?;
     bind_commands! {
     engine_state,
             All,
             Any,
             Append,
+            ChunkBy,
+            Chunks,
             Columns,
             Compact,
             Default,
@@ -106,10 +160,14 @@ pub fn bind_filter_commands(engine_state: &mut EngineState) -> CrateResult<()> {
             GroupBy,
             Headers,
             Insert,
+            Interleave,
+            IsEmpty,
+            IsNotEmpty,
             Items,
             Join,
             Take,
             Merge,
+            MergeDeep,
             Move,
             TakeWhile,
             TakeUntil,
@@ -124,13 +182,14 @@ pub fn bind_filter_commands(engine_state: &mut EngineState) -> CrateResult<()> {
             Rename,
             Reverse,
             Select,
-            Shuffle,
             Skip,
             SkipUntil,
             SkipWhile,
             Sort,
             SortBy,
+            SplitBy,
             SplitList,
+            Tee,
             Transpose,
             Uniq,
             UniqBy,
@@ -144,10 +203,12 @@ pub fn bind_filter_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     }
 }
 
+#[cfg(feature = "misc")]
 pub fn bind_misc_commands(engine_state: &mut EngineState) -> CrateResult<()> {
-    bind_commands!(engine_state, Tutor)
+    bind_commands!(engine_state, Panic, Source, Tutor)
 }
 
+#[cfg(feature = "path")]
 pub fn bind_path_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -159,12 +220,13 @@ pub fn bind_path_commands(engine_state: &mut EngineState) -> CrateResult<()> {
             PathJoin,
             PathParse,
             PathRelativeTo,
+            PathSelf,
             PathSplit,
             PathType,
     }
 }
 
-#[cfg(windows)]
+#[cfg(all(feature = "system", windows))]
 pub fn bind_system_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -172,13 +234,21 @@ pub fn bind_system_commands(engine_state: &mut EngineState) -> CrateResult<()> {
         External,
         NuCheck,
         Sys,
+        SysCpu,
+        SysDisks,
+        SysHost,
+        SysMem,
+        SysNet,
+        SysTemp,
+        SysUsers,
+        UName,
         Ps,
         Which,
         RegistryQuery
     }
 }
 
-#[cfg(unix)]
+#[cfg(all(feature = "system", unix))]
 pub fn bind_system_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -186,13 +256,22 @@ pub fn bind_system_commands(engine_state: &mut EngineState) -> CrateResult<()> {
         External,
         NuCheck,
         Sys,
+        SysCpu,
+        SysDisks,
+        SysHost,
+        SysMem,
+        SysNet,
+        SysTemp,
+        SysUsers,
+        UName,
         Ps,
         Which,
-        Exec
+        Exec,
+        ULimit,
     }
 }
 
-#[cfg(not(any(unix, windows)))]
+#[cfg(all(feature = "system", not(any(unix, windows))))]
 pub fn bind_system_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -200,21 +279,39 @@ pub fn bind_system_commands(engine_state: &mut EngineState) -> CrateResult<()> {
         External,
         NuCheck,
         Sys,
+        SysCpu,
+        SysDisks,
+        SysHost,
+        SysMem,
+        SysNet,
+        SysTemp,
+        SysUsers,
+        UName,
         Ps,
         Which
     }
 }
 
+#[cfg(feature = "string")]
 pub fn bind_string_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
             Char,
             Decode,
             Encode,
+            DecodeHex,
+            EncodeHex,
+            DecodeBase32,
+            EncodeBase32,
+            DecodeBase32Hex,
+            EncodeBase32Hex,
             DecodeBase64,
             EncodeBase64,
             DetectColumns,
             Format,
+            FormatDate,
+            FormatDuration,
+            FormatFilesize,
             Parse,
             Split,
             SplitChars,
@@ -227,11 +324,13 @@ pub fn bind_string_commands(engine_state: &mut EngineState) -> CrateResult<()> {
             StrDistance,
             StrDowncase,
             StrEndswith,
+            StrExpand,
             StrJoin,
             StrReplace,
             StrIndexOf,
             StrLength,
             StrReverse,
+            StrStats,
             StrStartsWith,
             StrSubstring,
             StrTrim,
@@ -239,6 +338,25 @@ pub fn bind_string_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     }
 }
 
+#[cfg(feature = "bit")]
+pub fn bind_bit_commands(engine_state: &mut EngineState) -> CrateResult<()> {
+    // the bits commands live in nu-cmd-extra instead of nu-command
+    bind_commands! {
+        engine_state,
+        nu_cmd_extra::Bits,
+        nu_cmd_extra::BitsAnd,
+        nu_cmd_extra::BitsInto,
+        nu_cmd_extra::BitsNot,
+        nu_cmd_extra::BitsOr,
+        nu_cmd_extra::BitsRol,
+        nu_cmd_extra::BitsRor,
+        nu_cmd_extra::BitsShl,
+        nu_cmd_extra::BitsShr,
+        nu_cmd_extra::BitsXor,
+    }
+}
+
+#[cfg(feature = "byte")]
 pub fn bind_byte_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -257,33 +375,50 @@ pub fn bind_byte_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     }
 }
 
+#[cfg(feature = "file-system")]
 pub fn bind_file_system_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
         Cd,
         UCp,
         Ls,
+        UMkdir,
+        Mktemp,
         UMv,
         Open,
+        Start,
         Rm,
+        Save,
+        Touch,
+        UTouch,
         Glob,
         Watch,
     }
 }
 
+#[cfg(feature = "platform")]
 pub fn bind_platform_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
         Ansi,
+        AnsiLink,
+        AnsiStrip,
         Clear,
         Du,
         Input,
+        InputList,
+        InputListen,
+        IsTerminal,
         Kill,
         Sleep,
+        Term,
         TermSize,
+        TermQuery,
+        Whoami,
     }
 }
 
+#[cfg(feature = "date")]
 pub fn bind_date_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -292,10 +427,13 @@ pub fn bind_date_commands(engine_state: &mut EngineState) -> CrateResult<()> {
         DateHumanize,
         DateListTimezones,
         DateNow,
+        DateToRecord,
+        DateToTable,
         DateToTimezone,
     }
 }
 
+#[cfg(feature = "shell")]
 pub fn bind_shell_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -303,12 +441,15 @@ pub fn bind_shell_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     }
 }
 
+#[cfg(feature = "format")]
 pub fn bind_format_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
             From,
             FromCsv,
             FromJson,
+            FromMsgpack,
+            FromMsgpackz,
             FromNuon,
             FromOds,
             FromSsv,
@@ -322,6 +463,8 @@ pub fn bind_format_commands(engine_state: &mut EngineState) -> CrateResult<()> {
             ToCsv,
             ToJson,
             ToMd,
+            ToMsgpack,
+            ToMsgpackz,
             ToNuon,
             ToText,
             ToToml,
@@ -333,6 +476,7 @@ pub fn bind_format_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     }
 }
 
+#[cfg(feature = "viewer")]
 pub fn bind_viewer_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -341,6 +485,7 @@ pub fn bind_viewer_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     }
 }
 
+#[cfg(feature = "conversion")]
 pub fn bind_conversion_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -348,15 +493,21 @@ pub fn bind_conversion_commands(engine_state: &mut EngineState) -> CrateResult<(
         Into,
         IntoBool,
         IntoBinary,
+        IntoCellPath,
         IntoDatetime,
         IntoDuration,
         IntoFilesize,
+        IntoFloat,
+        IntoGlob,
         IntoInt,
         IntoRecord,
         IntoString,
+        IntoValue,
+        SplitCellPath,
     }
 }
 
+#[cfg(feature = "environment")]
 pub fn bind_environment_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -370,6 +521,7 @@ pub fn bind_environment_commands(engine_state: &mut EngineState) -> CrateResult<
     }
 }
 
+#[cfg(feature = "math")]
 pub fn bind_math_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -392,6 +544,7 @@ pub fn bind_math_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     }
 }
 
+#[cfg(feature = "network")]
 pub fn bind_network_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -399,18 +552,40 @@ pub fn bind_network_commands(engine_state: &mut EngineState) -> CrateResult<()>
             HttpDelete,
             HttpGet,
             HttpHead,
+            HttpOptions,
             HttpPatch,
             HttpPost,
             HttpPut,
             Url,
             UrlBuildQuery,
+            UrlDecode,
             UrlEncode,
             UrlJoin,
             UrlParse,
+            UrlSplitQuery,
             Port,
     }
 }
 
+#[cfg(feature = "database")]
+pub fn bind_database_commands(engine_state: &mut EngineState) -> CrateResult<()> {
+    // the sqlite commands are private to nu-command and only bound through it
+    bind(engine_state, add_database_decls)?;
+    bind_commands! {
+        engine_state,
+        Stor,
+        StorCreate,
+        StorDelete,
+        StorExport,
+        StorImport,
+        StorInsert,
+        StorOpen,
+        StorReset,
+        StorUpdate,
+    }
+}
+
+#[cfg(feature = "random")]
 pub fn bind_random_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -418,10 +593,14 @@ pub fn bind_random_commands(engine_state: &mut EngineState) -> CrateResult<()> {
         RandomBool,
         RandomChars,
         RandomDice,
+        RandomFloat,
+        RandomInt,
         RandomUuid,
+        RandomBinary,
     }
 }
 
+#[cfg(feature = "generator")]
 pub fn bind_generator_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -429,9 +608,11 @@ pub fn bind_generator_commands(engine_state: &mut EngineState) -> CrateResult<()
         Seq,
         SeqDate,
         SeqChar,
+        Generate,
     }
 }
 
+#[cfg(feature = "hash")]
 pub fn bind_hash_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
@@ -441,6 +622,7 @@ pub fn bind_hash_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     }
 }
 
+#[cfg(feature = "experimental")]
 pub fn bind_experimental_commands(engine_state: &mut EngineState) -> CrateResult<()> {
     bind_commands! {
         engine_state,
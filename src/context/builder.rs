@@ -1,31 +1,73 @@
 use crate::{
-    error::CrateResult,
-    into_value::IntoValue,
-    utils::{parse_nu_script, NewEmpty},
+    callback::create_callback,
+    commands::{
+        ConfirmCommand, DerivedCommand, EmitCommand, ExternalInterceptor, FnCommand, InputCommand,
+        InputListCommand, InputListenCommand, OnCommand, OverrideCommand,
+    },
+    env_filter::EnvFilter,
+    error::{CrateError, CrateResult},
+    events::EventBus,
+    external::ExternalHandler,
+    host_data::{HostData, HostDataSlot},
+    host_fn::HostFn,
+    interaction::{InteractionProvider, NonInteractive},
+    into_value::{IntoValue, RawValue},
+    module::ModuleBuilder,
+    protection::{ProtectedDecls, ShadowPolicy},
+    struct_command::NuCommand,
+    utils::parse_nu_script,
+    variable::{register_var, VarOptions},
+};
+use std::{
+    env,
+    path::Path,
+    sync::{atomic::AtomicBool, Arc},
 };
-use std::env;
 
 use nu_protocol::{
     ast::Block,
     engine::{Command, EngineState, Stack, StateWorkingSet},
-    PipelineData, Span,
+    DeclId, PipelineData, ShellError, Signals, Value,
 };
 
-use super::{CommandGroupConfig, Context};
+use super::{
+    env::{path_to_list, validate_cwd},
+    CommandGroupConfig, Context,
+};
 
 /// Builder to create a new nu engine state
 pub struct ContextBuilder {
     engine_state: EngineState,
     stack: Stack,
     blocks: Vec<Block>,
+    overrides: Vec<(Vec<u8>, DeclId)>,
+    protected: ProtectedDecls,
+    data: HostData,
+    data_slot: HostDataSlot,
+    events: EventBus,
+    inherited_env: Vec<String>,
+    interaction: Arc<dyn InteractionProvider>,
+    cwd: Option<String>,
 }
 
 impl Default for ContextBuilder {
     fn default() -> Self {
+        let mut engine_state = EngineState::new();
+        engine_state.set_signals(Signals::new(Arc::new(AtomicBool::new(false))));
+        let data_slot = HostDataSlot::reserve(&mut engine_state);
+
         Self {
-            engine_state: EngineState::new(),
+            engine_state,
             stack: Stack::new(),
             blocks: Vec::new(),
+            overrides: Vec::new(),
+            protected: ProtectedDecls::default(),
+            data: HostData::default(),
+            data_slot,
+            events: EventBus::default(),
+            inherited_env: Vec::new(),
+            interaction: Arc::new(NonInteractive),
+            cwd: None,
         }
     }
 }
@@ -34,11 +76,18 @@ impl ContextBuilder {
     /// Enables certain command groups specified in the Config on the state
     pub fn with_command_groups(mut self, group_config: CommandGroupConfig) -> CrateResult<Self> {
         macro_rules! toggle_command_groups {
-            ($($group:ident),*) => {
+            ($($group:ident = $feature:literal),*) => {
                 paste::item!(
                 $(
                     if group_config.$group {
+                        #[cfg(feature = $feature)]
                         super::bindings::[<bind_ $group _commands>](&mut self.engine_state)?;
+
+                        #[cfg(not(feature = $feature))]
+                        return Err(CrateError::CommandGroupUnavailable(
+                            stringify!($group).into(),
+                            $feature.into(),
+                        ));
                     }
                 )*
                 )
@@ -46,30 +95,35 @@ impl ContextBuilder {
         }
 
         toggle_command_groups!(
-            core,
-            debug,
-            filter,
-            chart,
-            misc,
-            path,
-            system,
-            string,
-            byte,
-            file_system,
-            platform,
-            date,
-            shell,
-            format,
-            viewer,
-            conversion,
-            environment,
-            math,
-            network,
-            random,
-            generator,
-            hash,
-            experimental
+            core = "core",
+            debug = "debug",
+            filter = "filter",
+            chart = "chart",
+            misc = "misc",
+            path = "path",
+            system = "system",
+            string = "string",
+            bit = "bit",
+            byte = "byte",
+            file_system = "file-system",
+            platform = "platform",
+            date = "date",
+            shell = "shell",
+            format = "format",
+            viewer = "viewer",
+            conversion = "conversion",
+            environment = "environment",
+            math = "math",
+            network = "network",
+            database = "database",
+            random = "random",
+            generator = "generator",
+            hash = "hash",
+            experimental = "experimental"
         );
+        self.install_interaction()?;
+        self.apply_overrides()?;
+
         Ok(self)
     }
 
@@ -85,20 +139,178 @@ impl ContextBuilder {
         Ok(self)
     }
 
-    /// Adds a variable to the state
-    pub fn add_var<S: ToString, V: IntoValue>(mut self, name: S, value: V) -> CrateResult<Self> {
+    /// Adds a rust closure as a command with the given name.
+    /// The signature is generated from the types of the closures parameters.
+    /// `Option` parameters are optional and the returned value is converted back into a nu value
+    pub fn add_fn<S: ToString, Args, F: HostFn<Args>>(self, name: S, func: F) -> CrateResult<Self> {
+        self.add_command(FnCommand::new(name.to_string(), func))
+    }
+
+    /// Adds a command declared as struct with `#[derive(NuCommand)]`
+    pub fn add_nu_command<T: NuCommand>(self) -> CrateResult<Self> {
+        self.add_command(DerivedCommand::<T>::new())
+    }
+
+    /// Registers a module with host commands and constants
+    /// that scripts can import with `use`
+    pub fn add_module(mut self, module: ModuleBuilder) -> CrateResult<Self> {
+        module.register(&mut self.engine_state)?;
+
+        Ok(self)
+    }
+
+    /// Replaces the builtin command with the same name as the given command.
+    /// The signature of the builtin is kept so scripts are parsed the same way
+    /// and calls are served by the host command instead.
+    /// Errs if there's no command with that name yet
+    pub fn override_command<C: Command + 'static>(mut self, command: C) -> CrateResult<Self> {
+        let name = command.name().as_bytes().to_vec();
+        let original_id = self
+            .engine_state
+            .find_decl(&name, &[])
+            .ok_or_else(|| CrateError::FunctionNotFound(command.name().to_string()))?;
+        let command =
+            OverrideCommand::new(self.engine_state.get_decl(original_id), Box::new(command));
+        self.add_override(name, Box::new(command))?;
+
+        Ok(self)
+    }
+
+    /// Passes every external command invoked by scripts (like `^git status`)
+    /// to the given handler. The handler can deny the call, rewrite it
+    /// or answer it with synthetic output instead of spawning a process
+    pub fn on_external<H: ExternalHandler + 'static>(mut self, handler: H) -> CrateResult<Self> {
+        let command = ExternalInterceptor::new(Arc::new(handler));
+        self.add_override(command.name().as_bytes().to_vec(), Box::new(command))?;
+
+        Ok(self)
+    }
+
+    /// Routes the prompts of `input`, `input list` and `confirm` to the given provider
+    /// instead of reading from stdin. Contexts use [crate::NonInteractive] by default
+    /// so prompts fail instead of blocking. `input listen` always fails as it
+    /// reads the terminal directly
+    pub fn with_interaction<P: InteractionProvider + 'static>(
+        mut self,
+        provider: P,
+    ) -> CrateResult<Self> {
+        self.interaction = Arc::new(provider);
+        self.install_interaction()?;
+
+        Ok(self)
+    }
+
+    /// Replaces the bound interactive builtins with commands asking the interaction provider
+    fn install_interaction(&mut self) -> CrateResult<()> {
+        let builtins: [Box<dyn Command>; 3] = [
+            Box::new(InputCommand::new(Arc::clone(&self.interaction))),
+            Box::new(InputListCommand::new(Arc::clone(&self.interaction))),
+            Box::new(InputListenCommand),
+        ];
+        for command in builtins {
+            let name = command.name().as_bytes().to_vec();
+            if let Some(original_id) = self.engine_state.find_decl(&name, &[]) {
+                let command =
+                    OverrideCommand::new(self.engine_state.get_decl(original_id), command);
+                self.add_override(name, Box::new(command))?;
+            }
+        }
+        let confirm = ConfirmCommand::new(Arc::clone(&self.interaction));
+        self.add_override(confirm.name().as_bytes().to_vec(), Box::new(confirm))?;
+
+        Ok(())
+    }
+
+    fn add_override(&mut self, name: Vec<u8>, command: Box<dyn Command>) -> CrateResult<()> {
         let mut working_set = StateWorkingSet::new(&self.engine_state);
+        let decl_id = working_set.add_decl(command);
+        let delta = working_set.render();
+        self.engine_state.merge_delta(delta)?;
+        self.overrides.retain(|(n, _)| *n != name);
+        self.overrides.push((name, decl_id));
 
-        let var_id = working_set.add_variable(
-            name.to_string().into_bytes(),
-            Span::empty(),
-            nu_protocol::Type::Any,
-            false,
-        );
-        self.stack.add_var(var_id, value.into_value());
+        Ok(())
+    }
+
+    /// Puts the overriding commands back in scope after builtins
+    /// with the same names have been bound again
+    fn apply_overrides(&mut self) -> CrateResult<()> {
+        if self.overrides.is_empty() {
+            return Ok(());
+        }
+        let mut working_set = StateWorkingSet::new(&self.engine_state);
+        working_set.use_decls(self.overrides.clone());
         let delta = working_set.render();
         self.engine_state.merge_delta(delta)?;
 
+        Ok(())
+    }
+
+    /// Protects the command with the given name from being shadowed by scripts.
+    /// Violations of the policy are reported as parse errors
+    /// Errs if there's no command with that name
+    pub fn protect_command<S: AsRef<str>>(
+        mut self,
+        name: S,
+        policy: ShadowPolicy,
+    ) -> CrateResult<Self> {
+        let name = name.as_ref();
+        self.engine_state
+            .find_decl(name.as_bytes(), &[])
+            .ok_or_else(|| CrateError::FunctionNotFound(name.to_string()))?;
+        self.protected.insert(name.as_bytes().to_vec(), policy);
+
+        Ok(self)
+    }
+
+    /// Adds an immutable variable to the state.
+    /// Its type is inferred from the value
+    pub fn add_var<S: ToString, V: IntoValue>(self, name: S, value: V) -> CrateResult<Self> {
+        self.add_var_with(name, value, VarOptions::immutable())
+    }
+
+    /// Adds a variable with the given type, mutability or const-ness to the state
+    pub fn add_var_with<S: ToString, V: IntoValue>(
+        mut self,
+        name: S,
+        value: V,
+        options: VarOptions,
+    ) -> CrateResult<Self> {
+        register_var(
+            &mut self.engine_state,
+            &mut self.stack,
+            name.to_string(),
+            value.into_value(),
+            options,
+        )?;
+
+        Ok(self)
+    }
+
+    /// Adds a variable holding a closure that calls the given rust function.
+    /// See [Context::create_callback]
+    pub fn add_callback_var<S, F>(mut self, name: S, func: F) -> CrateResult<Self>
+    where
+        S: ToString,
+        F: Fn(Vec<Value>, PipelineData) -> Result<PipelineData, ShellError> + Send + Sync + 'static,
+    {
+        let closure = create_callback(&mut self.engine_state, &mut self.stack, Arc::new(func))?;
+
+        self.add_var(name, RawValue(closure))
+    }
+
+    /// Sets the working directory of the context.
+    /// Commands like `ls` or `open` resolve relative paths against it
+    /// instead of the working directory of the process. It takes precedence
+    /// over an inherited `PWD` regardless of the order of the builder calls.
+    /// Scripts added afterwards are parsed in it, so they can source files relative to it.
+    /// Errs if the path isn't an absolute path to an existing directory
+    pub fn cwd<P: AsRef<Path>>(mut self, path: P) -> CrateResult<Self> {
+        let cwd = validate_cwd(path.as_ref())?;
+        self.engine_state
+            .add_env_var(String::from("PWD"), cwd.clone().into_value());
+        self.cwd = Some(cwd);
+
         Ok(self)
     }
 
@@ -112,14 +324,59 @@ impl ContextBuilder {
 
     /// Adds the environment variables of the parent process to the
     /// states env variables
+    /// See [ContextBuilder::add_parent_env_vars_filtered]
     pub fn add_parent_env_vars(self) -> Self {
-        let mut builder = self;
+        self.add_parent_env_vars_filtered(&EnvFilter::new())
+    }
 
+    /// Adds the environment variables of the parent process that match the filter.
+    /// `PATH` is converted into a list. Conversions of `$env.ENV_CONVERSIONS`
+    /// set by the scripts of the builder are applied when building the context.
+    /// `PWD` is replaced with the current directory if it isn't an absolute path
+    /// to an existing directory
+    pub fn add_parent_env_vars_filtered(mut self, filter: &EnvFilter) -> Self {
         for (name, val) in env::vars() {
-            builder = builder.add_env_var(name, val);
+            if !filter.matches(&name, &val) {
+                continue;
+            }
+            let value = if name == "PWD" {
+                if self.cwd.is_some() {
+                    continue;
+                }
+                match valid_pwd(val) {
+                    Some(pwd) => pwd.into_value(),
+                    None => continue,
+                }
+            } else if name.eq_ignore_ascii_case("path") {
+                path_to_list(&val)
+            } else {
+                val.into_value()
+            };
+            self.engine_state.add_env_var(name.clone(), value);
+            self.inherited_env.push(name);
         }
 
-        builder
+        self
+    }
+
+    /// Enables the `emit` and `on` commands.
+    /// Scripts can emit events with `emit <event> <payload>` that are delivered
+    /// to the subscribers of the context and register handlers with `on <event> {|e| ...}`
+    /// that are called when the host triggers the event
+    pub fn with_events(mut self) -> CrateResult<Self> {
+        self.events.enable();
+        let emit = EmitCommand::new(self.events.clone());
+        let on = OnCommand::new(self.events.clone());
+
+        self.add_command(emit)?.add_command(on)
+    }
+
+    /// Adds typed data of the host that can be accessed by commands.
+    /// Data of the same type replaces the previous value
+    pub fn with_data<T: Send + Sync + 'static>(mut self, data: T) -> Self {
+        self.data.insert(data);
+
+        self
     }
 
     /// Adds a block to the builder
@@ -127,6 +384,7 @@ impl ContextBuilder {
     /// the blocks contents in scope.
     /// Note: Code not contained in declarations is being executed when building
     ///       the context
+    /// Building fails if the block declares or hides protected commands
     pub fn add_block(mut self, block: Block) -> Self {
         self.blocks.push(block);
 
@@ -136,22 +394,50 @@ impl ContextBuilder {
     /// Adds a script to the context.
     /// This script is being parsed so this operation can fail
     pub fn add_script(mut self, contents: String) -> CrateResult<Self> {
-        let block = parse_nu_script(&mut self.engine_state, contents)?;
+        let block = parse_nu_script(&mut self.engine_state, contents, &self.protected)?;
         self.blocks.push(block);
 
         Ok(self)
     }
 
     /// builds the context
-    pub fn build(self) -> CrateResult<Context> {
+    pub fn build(mut self) -> CrateResult<Context> {
+        let errors = self
+            .blocks
+            .iter()
+            .flat_map(|block| self.protected.check_block(&self.engine_state, block))
+            .collect::<Vec<_>>();
+
+        if !errors.is_empty() {
+            return Err(CrateError::NuParseErrors(errors));
+        }
+        // applied again so the working directory wins over a `PWD` set with `add_env_var`
+        if let Some(pwd) = self.cwd.take() {
+            self.engine_state
+                .add_env_var(String::from("PWD"), pwd.into_value());
+        }
+        self.data_slot.fill(self.data);
+
         let mut ctx = Context {
-            engine_state: self.engine_state,
+            engine_state: Arc::new(self.engine_state),
             stack: self.stack,
+            protected: self.protected,
+            events: self.events,
         };
         for block in self.blocks {
             ctx.eval_block(&block, PipelineData::empty())?;
         }
+        ctx.apply_env_conversions(&self.inherited_env)?;
 
         Ok(ctx)
     }
 }
+
+/// Returns the inherited working directory if it's an absolute path
+/// to an existing directory and falls back to the current directory otherwise
+fn valid_pwd(pwd: String) -> Option<String> {
+    validate_cwd(Path::new(&pwd)).ok().or_else(|| {
+        let dir = env::current_dir().ok()?;
+        validate_cwd(&dir).ok()
+    })
+}
@@ -0,0 +1,151 @@
+use std::{collections::HashMap, sync::Arc};
+
+use nu_protocol::{
+    DeclId, PipelineData, Value,
+    engine::{EngineState, Stack, StateWorkingSet},
+};
+
+use crate::{argument::IntoArgument, error::CrateResult};
+
+use super::{Context, build_call};
+
+/// Controls which changes a function call makes to the stack of the context
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallMode {
+    /// The function runs on a copy of the stack.
+    /// All changes to the environment are discarded, even those of `def --env` functions
+    Isolated,
+    /// Changes to `$env` are kept, even if the function isn't declared with `def --env`
+    PreserveEnv,
+}
+
+/// The changes a function call made to the environment
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EnvDiff {
+    /// Variables that didn't exist before the call
+    pub added: HashMap<String, Value>,
+    /// Variables that got a new value
+    pub changed: HashMap<String, Value>,
+    /// Variables that were removed by the call
+    pub removed: Vec<String>,
+}
+
+impl EnvDiff {
+    fn new(before: &HashMap<String, Value>, mut after: HashMap<String, Value>) -> Self {
+        let mut diff = Self::default();
+
+        for (name, old_value) in before {
+            match after.remove(name) {
+                Some(value) if value != *old_value => {
+                    diff.changed.insert(name.clone(), value);
+                }
+                Some(_) => {}
+                None => diff.removed.push(name.clone()),
+            }
+        }
+        diff.added = after;
+
+        diff
+    }
+
+    /// Returns if the call didn't change the environment
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl Context {
+    /// Calls a function by the given name with the given [CallMode]
+    /// Errs if the function doesn't exist
+    pub fn call_fn_with<S: AsRef<str>, I: IntoIterator<Item = A>, A: IntoArgument>(
+        &mut self,
+        name: S,
+        args: I,
+        mode: CallMode,
+    ) -> CrateResult<PipelineData> {
+        let preserve_env = mode == CallMode::PreserveEnv;
+        let (data, stack) = self.call_on_copy(name, args, preserve_env)?;
+
+        if preserve_env {
+            self.stack = stack;
+        }
+
+        Ok(data)
+    }
+
+    /// Calls a function by the given name with the given [CallMode]
+    /// and returns the changes it made to `$env` alongside its output.
+    /// The changes are reported for both modes
+    pub fn call_fn_env_diff<S: AsRef<str>, I: IntoIterator<Item = A>, A: IntoArgument>(
+        &mut self,
+        name: S,
+        args: I,
+        mode: CallMode,
+    ) -> CrateResult<(PipelineData, EnvDiff)> {
+        let (data, stack) = self.call_on_copy(name, args, true)?;
+        let diff = EnvDiff::new(
+            &self.stack.get_env_vars(&self.engine_state),
+            stack.get_env_vars(&self.engine_state),
+        );
+
+        if mode == CallMode::PreserveEnv {
+            self.stack = stack;
+        }
+
+        Ok((data, diff))
+    }
+
+    /// Calls the function on a copy of the stack and returns the stack after the call.
+    /// With `redirect_env` the environment of the function is passed back
+    /// like for functions declared with `def --env`
+    fn call_on_copy<S: AsRef<str>, I: IntoIterator<Item = A>, A: IntoArgument>(
+        &self,
+        name: S,
+        args: I,
+        redirect_env: bool,
+    ) -> CrateResult<(PipelineData, Stack)> {
+        let mut call = build_call(&self.engine_state, name, args)?;
+        let mut engine_state = Arc::clone(&self.engine_state);
+
+        let redirected = if redirect_env {
+            with_redirected_env(&mut engine_state, call.call.decl_id)?
+        } else {
+            None
+        };
+        if let Some(decl_id) = redirected {
+            call.call.decl_id = decl_id;
+        }
+        let mut stack = self.stack.clone();
+        let data = call.eval(&engine_state, &mut stack)?;
+
+        Ok((data, stack))
+    }
+}
+
+/// Adds a copy of the custom command that passes its environment back to the caller.
+/// The copy is only added to the given engine state which is cloned on write
+/// so the definitions of the context stay untouched.
+/// Returns `None` if the command already does so or isn't a custom command
+fn with_redirected_env(
+    engine_state: &mut Arc<EngineState>,
+    decl_id: DeclId,
+) -> CrateResult<Option<DeclId>> {
+    let decl = engine_state.get_decl(decl_id);
+    let Some(block_id) = decl.block_id() else {
+        return Ok(None);
+    };
+    let block = engine_state.get_block(block_id);
+    if block.redirect_env {
+        return Ok(None);
+    }
+    let mut block = (**block).clone();
+    block.redirect_env = true;
+
+    let mut working_set = StateWorkingSet::new(engine_state);
+    let block_id = working_set.add_block(Arc::new(block));
+    let decl_id = working_set.add_decl(decl.signature().into_block_command(block_id));
+    let delta = working_set.render();
+    Arc::make_mut(engine_state).merge_delta(delta)?;
+
+    Ok(Some(decl_id))
+}
@@ -1,6 +1,6 @@
 macro_rules! command_group_config {
 
-    ($(#[doc=$doc:literal] $group:ident),*) => {
+    ($(#[doc=$doc:literal] $group:ident = $feature:literal),*) => {
 
         /// Enables or disables certain command groups
         #[derive(Clone, Debug, Default)]
@@ -10,9 +10,10 @@ macro_rules! command_group_config {
 
         impl CommandGroupConfig {
             /// Enables all commands
+            /// Groups whose cargo feature is disabled are skipped
             pub fn all_groups(mut self, enabled: bool) -> Self {
                 $(
-                    self.$group = enabled;
+                    self.$group = enabled && cfg!(feature = $feature);
                 )*
 
                 self
@@ -21,6 +22,8 @@ macro_rules! command_group_config {
             $(
             paste::item! {
                 #[doc=$doc]
+                #[doc=""]
+                #[doc=concat!("Requires the `", $feature, "` feature")]
                 #[inline]
                 pub fn [< $group _group>](mut self, enabled: bool) -> Self {
                     self.$group = enabled;
@@ -35,51 +38,53 @@ macro_rules! command_group_config {
 
 command_group_config!(
     /// Enables core commands
-    core,
+    core = "core",
     /// Enables debug commands
-    debug,
+    debug = "debug",
     /// Enables filter commands
-    filter,
+    filter = "filter",
     /// Enables chart commands
-    chart,
+    chart = "chart",
     /// Enables misc commands
-    misc,
+    misc = "misc",
     /// Enables commands that allow path manipulation
-    path,
+    path = "path",
     /// Enables system commands
-    system,
+    system = "system",
     /// Enables commands to manipulate strings
-    string,
+    string = "string",
     /// Enables commands to manipulate bits
-    bit,
+    bit = "bit",
     /// Enables commands to manipulate bytes
-    byte,
+    byte = "byte",
     /// Enables commands that allow file system operations
-    file_system,
+    file_system = "file-system",
     /// Enables commands that allow using shell features like ansi colors
-    platform,
+    platform = "platform",
     /// Enables commands that allow datetime manipulation
-    date,
+    date = "date",
     /// Enables commands that allow creating and switching between nu shell instances
-    shell,
+    shell = "shell",
     /// Enables commands that allow parsing from one data format to another
-    format,
+    format = "format",
     /// Enables commands that allow displaying data in certain viewers like a table or grid
-    viewer,
+    viewer = "viewer",
     /// Enables commands that allow converting from one data format to another
-    conversion,
+    conversion = "conversion",
     /// Enables commands that allow manipulating environment variables
-    environment,
+    environment = "environment",
     /// Enables math related commands
-    math,
+    math = "math",
     /// Enables commands that allow networking
-    network,
+    network = "network",
+    /// Enables commands that work with sqlite databases like `stor` and `query db`
+    database = "database",
     /// Enables commands that generate random values
-    random,
+    random = "random",
     /// Enables commands that generate values for a given input
-    generator,
+    generator = "generator",
     /// Enables commands that work with hash sums
-    hash,
+    hash = "hash",
     /// Enables commands that are still experimental like `is-admin` and `view-source`
-    experimental
+    experimental = "experimental"
 );
@@ -0,0 +1,135 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use nu_engine::ClosureEvalOnce;
+use nu_protocol::{Span, Value, engine::Closure};
+
+use crate::{
+    IntoValue, NewEmpty, RawValue,
+    error::{CrateError, CrateResult},
+};
+
+use super::Context;
+
+impl Context {
+    /// Returns the environment variable with the given name
+    /// as seen by scripts evaluated in the context
+    pub fn get_env<S: AsRef<str>>(&self, name: S) -> Option<Value> {
+        self.stack
+            .get_env_var(&self.engine_state, name.as_ref())
+            .cloned()
+    }
+
+    /// Returns the environment variable with the given name converted into a string
+    /// like it is passed to external commands. Uses the `to_string` conversion of
+    /// `$env.ENV_CONVERSIONS` if there is one, lists like `PATH` are joined
+    /// with the path separator of the platform
+    pub fn get_env_string<S: AsRef<str>>(&self, name: S) -> CrateResult<Option<String>> {
+        let name = name.as_ref();
+        let Some(value) = self.get_env(name) else {
+            return Ok(None);
+        };
+        let string = nu_engine::env_to_string(name, &value, &self.engine_state, &self.stack)?;
+
+        Ok(Some(string))
+    }
+
+    /// Sets the environment variable for all following evaluations.
+    /// String values are converted with the `from_string` conversion
+    /// of `$env.ENV_CONVERSIONS`. `PATH` is split into a list if there's no conversion for it
+    pub fn set_env<S: ToString, V: IntoValue>(&mut self, name: S, value: V) -> CrateResult<()> {
+        let name = name.to_string();
+        let value = self.env_from_string(&name, value.into_value())?;
+        self.stack.add_env_var(name, value);
+
+        Ok(())
+    }
+
+    /// Removes the environment variable and returns its previous value
+    pub fn remove_env<S: AsRef<str>>(&mut self, name: S) -> Option<Value> {
+        let value = self.get_env(name.as_ref())?;
+        self.stack.remove_env_var(&self.engine_state, name.as_ref());
+
+        Some(value)
+    }
+
+    /// Returns the working directory of the context
+    pub fn cwd(&self) -> Option<PathBuf> {
+        self.get_env("PWD")?.as_str().ok().map(PathBuf::from)
+    }
+
+    /// Changes the working directory of the context by setting `$env.PWD`.
+    /// Errs if the path isn't an absolute path to an existing directory
+    pub fn set_cwd<P: AsRef<Path>>(&mut self, path: P) -> CrateResult<()> {
+        let pwd = validate_cwd(path.as_ref())?;
+        self.stack
+            .add_env_var(String::from("PWD"), pwd.into_value());
+
+        Ok(())
+    }
+
+    /// Returns all environment variables visible to scripts
+    pub fn env_vars(&self) -> HashMap<String, Value> {
+        self.stack.get_env_vars(&self.engine_state)
+    }
+
+    fn env_from_string(&self, name: &str, value: Value) -> CrateResult<Value> {
+        let Value::String { val, .. } = &value else {
+            return Ok(value);
+        };
+
+        match self.env_conversion(name, "from_string") {
+            Some(closure) => ClosureEvalOnce::new(&self.engine_state, &self.stack, closure)
+                .run_with_value(value)?
+                .into_value(Span::empty())
+                .map_err(CrateError::from),
+            None if name.eq_ignore_ascii_case("path") => Ok(path_to_list(val)),
+            None => Ok(value),
+        }
+    }
+
+    /// Converts the inherited environment variables with the `from_string`
+    /// conversion of `$env.ENV_CONVERSIONS` if there is one
+    pub(super) fn apply_env_conversions(&mut self, names: &[String]) -> CrateResult<()> {
+        for name in names {
+            let Some(value) = self.get_env(name) else {
+                continue;
+            };
+            if value.as_str().is_ok() && self.env_conversion(name, "from_string").is_some() {
+                self.set_env(name, RawValue(value))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the closure converting the environment variable
+    /// in the given direction from `$env.ENV_CONVERSIONS`
+    fn env_conversion(&self, name: &str, direction: &str) -> Option<Closure> {
+        let conversions = self.get_env("ENV_CONVERSIONS")?;
+        let conversion = conversions.get_data_by_key(name)?;
+        let closure = conversion.get_data_by_key(direction)?;
+
+        closure.as_closure().ok().cloned()
+    }
+}
+
+/// Splits a path variable like `PATH` into a list of paths
+pub(super) fn path_to_list(paths: &str) -> Value {
+    let paths = std::env::split_paths(paths)
+        .map(|path| Value::string(path.to_string_lossy(), Span::empty()))
+        .collect();
+
+    Value::list(paths, Span::empty())
+}
+
+/// Returns the path as string if it's an absolute path to an existing directory
+pub(super) fn validate_cwd(path: &Path) -> CrateResult<String> {
+    if path.is_absolute() && path.is_dir() {
+        Ok(path.to_string_lossy().into_owned())
+    } else {
+        Err(CrateError::InvalidCwd(path.to_path_buf()))
+    }
+}
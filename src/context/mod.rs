@@ -1,30 +1,104 @@
+#[cfg(feature = "async")]
+mod async_eval;
+// only used by the command groups enabled with features
+#[cfg(any(
+    feature = "core",
+    feature = "debug",
+    feature = "filter",
+    feature = "chart",
+    feature = "misc",
+    feature = "path",
+    feature = "system",
+    feature = "string",
+    feature = "bit",
+    feature = "byte",
+    feature = "file-system",
+    feature = "platform",
+    feature = "date",
+    feature = "shell",
+    feature = "format",
+    feature = "viewer",
+    feature = "conversion",
+    feature = "environment",
+    feature = "math",
+    feature = "network",
+    feature = "database",
+    feature = "random",
+    feature = "generator",
+    feature = "hash",
+    feature = "experimental"
+))]
 mod bindings;
 mod builder;
+mod call_mode;
 mod command_group_config;
-use std::collections::HashMap;
+mod env;
+mod shared;
+mod snapshot;
+mod transaction;
+mod vars;
+use std::{
+    collections::HashMap,
+    io::Read,
+    sync::{mpsc::Receiver, Arc},
+};
 
+#[cfg(feature = "async")]
+pub use async_eval::ValueStream;
 pub use builder::*;
+pub use call_mode::{CallMode, EnvDiff};
 pub use command_group_config::CommandGroupConfig;
+use nu_engine::ClosureEvalOnce;
 use nu_protocol::{
     ast::{Block, Call},
     engine::{EngineState, Stack, StateWorkingSet},
-    PipelineData, Span,
+    FromValue, PipelineData, PipelineIterator, ShellError, Signals, Span, Value,
 };
+pub use shared::SharedContext;
+pub use snapshot::ContextSnapshot;
 
 use crate::{
     argument::IntoArgument,
+    callback::create_callback,
+    commands::{EmitCommand, OnCommand},
     error::{CrateError, CrateResult},
+    events::{Event, EventBus},
+    into_expression::Captures,
+    protection::ProtectedDecls,
+    stream::{byte_stream, list_stream},
     utils::parse_nu_script,
+    value_iter::ValueIter,
+    variable::{find_var, register_var, VarOptions},
     IntoValue, NewEmpty,
 };
 
 /// Represents the evaluation context of nu scripts and commands
 /// This context is the state of the engine itself plus the stack
 /// It stores variables on
-#[derive(Clone)]
+/// Cloning a context copies its stack and definitions and gives the clone
+/// its own event bus with a copy of the subscribers and handlers.
+/// Use [Context::fork] or [Context::snapshot] to create isolated copies cheaply
 pub struct Context {
-    engine_state: EngineState,
+    engine_state: Arc<EngineState>,
     stack: Stack,
+    protected: ProtectedDecls,
+    events: EventBus,
+}
+
+impl Clone for Context {
+    /// Clones get an event bus of their own so handlers registered with `on`
+    /// are only triggered in the context that knows their closures
+    fn clone(&self) -> Self {
+        let mut ctx = Self {
+            engine_state: Arc::clone(&self.engine_state),
+            stack: self.stack.clone(),
+            protected: self.protected.clone(),
+            events: self.events.clone(),
+        };
+        ctx.fork_events();
+
+        ctx
+    }
 }
 
 impl Context {
@@ -49,27 +123,44 @@ impl Context {
         contents: S,
         input: PipelineData,
     ) -> CrateResult<PipelineData> {
-        let block = parse_nu_script(&mut self.engine_state, contents.to_string())?;
+        let block = parse_nu_script(
+            Arc::make_mut(&mut self.engine_state),
+            contents.to_string(),
+            &self.protected,
+        )?;
 
         self.eval_block(&block, input)
     }
 
+    /// Evals nu script as string and returns an iterator that lazily
+    /// converts the output values into `T`.
+    /// Errors raised while streaming the output are returned as `Err` items
+    pub fn eval_iter<T: FromValue, S: ToString>(
+        &mut self,
+        contents: S,
+        input: PipelineData,
+    ) -> CrateResult<ValueIter<T>> {
+        let pipeline = self.eval_raw(contents, input)?;
+
+        Ok(self.iter_as(pipeline))
+    }
+
     /// Returns a variable defined in the stack
     pub fn get_var<S: AsRef<str>>(&self, name: S) -> Option<nu_protocol::Value> {
-        let name = name.as_ref();
-        let dollar_name = format!("${name}");
-        let var_id = self.engine_state.active_overlays(&vec![]).find_map(|o| {
-            o.vars
-                .get(dollar_name.as_bytes())
-                .or(o.vars.get(name.as_bytes()))
-        })?;
-        self.stack.get_var(*var_id, Span::new(0, 0)).ok()
+        let var_id = find_var(&self.engine_state, name.as_ref())?;
+        self.stack.get_var(var_id, Span::new(0, 0)).ok()
+    }
+
+    /// Returns the host data of the given type
+    /// that has been added with [ContextBuilder::with_data]
+    pub fn data<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        crate::host_data::host_data(&self.engine_state)
     }
 
     /// Returns if the given function exists in the context
     pub fn has_fn<S: AsRef<str>>(&mut self, name: S) -> bool {
         self.engine_state
-            .find_decl(name.as_ref().as_bytes(), &vec![])
+            .find_decl(name.as_ref().as_bytes(), &[])
             .is_some()
     }
 
@@ -80,30 +171,43 @@ impl Context {
         name: S,
         args: I,
     ) -> CrateResult<PipelineData> {
-        let args = args
-            .into_iter()
-            .map(|a| a.into_argument().into_nu_argument())
-            .collect::<Vec<_>>();
+        let call = build_call(&self.engine_state, name, args)?;
+        let data = call.eval(&self.engine_state, &mut self.stack)?;
 
-        let decl_id = self
-            .engine_state
-            .find_decl(name.as_ref().as_bytes(), &vec![])
-            .ok_or_else(|| CrateError::FunctionNotFound(name.as_ref().to_string()))?;
-        let call = Call {
-            decl_id,
-            head: Span::empty(),
-            arguments: args,
-            parser_info: HashMap::new(),
-        };
+        Ok(data)
+    }
 
-        let data = nu_engine::eval_call::<nu_protocol::debugger::WithoutDebug>(
-            &self.engine_state,
-            &mut self.stack,
-            &call,
-            PipelineData::empty(),
-        )?;
+    /// Returns the signals used to interrupt evaluations and streams of this context
+    pub fn signals(&self) -> &Signals {
+        self.engine_state.signals()
+    }
 
-        Ok(data)
+    /// Creates a lazy stream from the given iterator that can be passed as input
+    /// The stream stops when the context is interrupted
+    pub fn stream_iter<I, V>(&self, iter: I) -> PipelineData
+    where
+        I: IntoIterator<Item = V>,
+        I::IntoIter: Send + 'static,
+        V: IntoValue,
+    {
+        list_stream(iter, Span::empty(), self.signals().clone())
+    }
+
+    /// Creates a lazy byte stream from the given reader that can be passed as input
+    /// The stream stops when the context is interrupted
+    pub fn stream_reader<R: Read + Send + 'static>(&self, reader: R) -> PipelineData {
+        byte_stream(reader, Span::empty(), self.signals().clone())
+    }
+
+    /// Returns an iterator that lazily pulls the values of the given pipeline
+    pub fn iter_values(&self, pipeline: PipelineData) -> PipelineIterator {
+        pipeline.into_iter()
+    }
+
+    /// Returns an iterator that lazily pulls the values of the given pipeline
+    /// and converts them into `T`
+    pub fn iter_as<T: FromValue>(&self, pipeline: PipelineData) -> ValueIter<T> {
+        ValueIter::new(pipeline.into_iter())
     }
 
     /// Prints the data of the given pipeline to stdout
@@ -120,20 +224,166 @@ impl Context {
         Ok(())
     }
 
-    /// Adds a variable to the context
+    /// Creates a closure value that calls the given rust function.
+    /// The value can be passed to scripts as variable or function argument
+    /// and be called like any other closure with `do $callback` or `each $callback`
+    pub fn create_callback<F>(&mut self, func: F) -> CrateResult<Value>
+    where
+        F: Fn(Vec<Value>, PipelineData) -> Result<PipelineData, ShellError> + Send + Sync + 'static,
+    {
+        create_callback(
+            Arc::make_mut(&mut self.engine_state),
+            &mut self.stack,
+            Arc::new(func),
+        )
+    }
+
+    /// Calls the callback for every event with the given name that is emitted by scripts.
+    /// Events are only emitted when the context was built with [ContextBuilder::with_events]
+    pub fn subscribe<S, F>(&self, name: S, callback: F)
+    where
+        S: ToString,
+        F: Fn(&Event) + Send + Sync + 'static,
+    {
+        self.events.subscribe(name.to_string(), Arc::new(callback));
+    }
+
+    /// Returns a channel that receives every event with the given name emitted by scripts
+    pub fn subscribe_channel<S: ToString>(&self, name: S) -> Receiver<Event> {
+        self.events.subscribe_channel(name.to_string())
+    }
+
+    /// Calls the handlers scripts registered for the event with `on <event> {|e| ...}`
+    /// Returns the output of every handler
+    pub fn trigger<S: AsRef<str>, V: IntoValue>(
+        &mut self,
+        name: S,
+        payload: V,
+    ) -> CrateResult<Vec<PipelineData>> {
+        let payload = payload.into_value();
+
+        self.events
+            .handlers(name.as_ref())
+            .into_iter()
+            .map(|closure| {
+                ClosureEvalOnce::new(&self.engine_state, &self.stack, closure)
+                    .run_with_value(payload.clone())
+                    .map_err(CrateError::from)
+            })
+            .collect()
+    }
+
+    /// Adds an immutable variable to the context.
+    /// Its type is inferred from the value
     pub fn add_var<S: ToString, V: IntoValue>(&mut self, name: S, value: V) -> CrateResult<()> {
-        let mut working_set = StateWorkingSet::new(&self.engine_state);
+        self.add_var_with(name, value, VarOptions::immutable())
+    }
 
-        let var_id = working_set.add_variable(
-            name.to_string().into_bytes(),
-            Span::empty(),
-            nu_protocol::Type::Any,
-            false,
-        );
-        self.stack.add_var(var_id, value.into_value());
-        let delta = working_set.render();
-        self.engine_state.merge_delta(delta)?;
+    /// Adds a variable with the given type, mutability or const-ness to the context
+    pub fn add_var_with<S: ToString, V: IntoValue>(
+        &mut self,
+        name: S,
+        value: V,
+        options: VarOptions,
+    ) -> CrateResult<()> {
+        register_var(
+            Arc::make_mut(&mut self.engine_state),
+            &mut self.stack,
+            name.to_string(),
+            value.into_value(),
+            options,
+        )?;
 
         Ok(())
     }
 }
+
+/// The call of a function with arguments provided by the host
+pub(super) struct HostCall {
+    pub call: Call,
+    captures: Captures,
+}
+
+impl HostCall {
+    /// Evaluates the call with the values captured by closures in its arguments
+    /// bound on the stack. The variables get their previous values back afterwards
+    pub fn eval(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+    ) -> Result<PipelineData, ShellError> {
+        let previous = self
+            .captures
+            .iter()
+            .map(|(var_id, value)| {
+                let old = stack.get_var(*var_id, Span::empty()).ok();
+                stack.add_var(*var_id, value.clone());
+                (*var_id, old)
+            })
+            .collect::<Vec<_>>();
+        let data = nu_engine::eval_call::<nu_protocol::debugger::WithoutDebug>(
+            engine_state,
+            stack,
+            &self.call,
+            PipelineData::empty(),
+        );
+        for (var_id, old) in previous.into_iter().rev() {
+            match old {
+                Some(value) => stack.add_var(var_id, value),
+                None => stack.remove_var(var_id),
+            }
+        }
+
+        data
+    }
+}
+
+impl Context {
+    /// Gives the context its own event bus so handlers registered
+    /// with `on` don't leak into the context it has been copied from
+    fn fork_events(&mut self) {
+        if !self.events.is_enabled() {
+            return;
+        }
+        self.events = self.events.fork();
+        let mut working_set = StateWorkingSet::new(&self.engine_state);
+        working_set.add_decl(Box::new(EmitCommand::new(self.events.clone())));
+        working_set.add_decl(Box::new(OnCommand::new(self.events.clone())));
+        let delta = working_set.render();
+        Arc::make_mut(&mut self.engine_state)
+            .merge_delta(delta)
+            .expect("a delta with only new declarations can always be merged");
+    }
+}
+
+/// Creates the call of a function by the given name
+/// Errs if the function doesn't exist
+pub(super) fn build_call<S: AsRef<str>, I: IntoIterator<Item = A>, A: IntoArgument>(
+    engine_state: &EngineState,
+    name: S,
+    args: I,
+) -> CrateResult<HostCall> {
+    let mut captures = Vec::new();
+    let args = args
+        .into_iter()
+        .map(|a| {
+            let (argument, arg_captures) = a.into_argument_with_captures();
+            captures.extend(arg_captures);
+            argument.into_nu_argument()
+        })
+        .collect::<Vec<_>>();
+
+    let decl_id = engine_state
+        .find_decl(name.as_ref().as_bytes(), &[])
+        .ok_or_else(|| CrateError::FunctionNotFound(name.as_ref().to_string()))?;
+
+    Ok(HostCall {
+        call: Call {
+            decl_id,
+            head: Span::empty(),
+            arguments: args,
+            parser_info: HashMap::new(),
+        },
+        captures,
+    })
+}
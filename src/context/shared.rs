@@ -0,0 +1,198 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use nu_protocol::{
+    BlockId, PipelineData,
+    ast::Block,
+    debugger::WithoutDebug,
+    engine::{EngineState, StateWorkingSet},
+    ir::Instruction,
+};
+
+use crate::{argument::IntoArgument, error::CrateResult, utils::parse_in_working_set};
+
+use super::{Context, ContextSnapshot, build_call};
+
+/// The number of scripts with own definitions whose parsed state is kept
+const MAX_CACHED_SCRIPTS: usize = 64;
+
+/// A context that can be used from many threads at the same time.
+/// All evaluations share the parsed definitions of the context
+/// and get their own stack, so changes made by one evaluation
+/// are never visible to the others.
+///
+/// Scripts are parsed on top of the shared definitions. Scripts that
+/// define commands, closures or modules need a copy of the definitions,
+/// which is cached for the next evaluation of the same script.
+///
+/// With events enabled, scripts registering handlers with `on` run in a fork
+/// of the context so the handlers stay with the evaluation. Forking copies the
+/// definitions on every evaluation. If the shared definitions register handlers
+/// themselves, every evaluation and function call is run in a fork
+#[derive(Clone)]
+pub struct SharedContext {
+    snapshot: ContextSnapshot,
+    scripts: Arc<RwLock<HashMap<String, ParsedScript>>>,
+    registers_handlers: bool,
+}
+
+/// A script together with the state its blocks can be evaluated in
+#[derive(Clone)]
+struct ParsedScript {
+    engine_state: Arc<EngineState>,
+    block: Arc<Block>,
+    registers_handlers: bool,
+}
+
+impl SharedContext {
+    pub fn new(snapshot: ContextSnapshot) -> Self {
+        let engine_state = snapshot.engine_state();
+        let registers_handlers = snapshot.has_events()
+            && (0..engine_state.num_blocks()).any(|id| {
+                registers_handlers(engine_state, engine_state.get_block(BlockId::new(id)))
+            });
+
+        Self {
+            snapshot,
+            scripts: Arc::default(),
+            registers_handlers,
+        }
+    }
+
+    /// Evals nu script as string with its own stack
+    pub fn eval_raw<S: ToString>(
+        &self,
+        contents: S,
+        input: PipelineData,
+    ) -> CrateResult<PipelineData> {
+        if self.registers_handlers {
+            return self.fork()?.eval_raw(contents, input);
+        }
+        let script = self.parse(contents.to_string())?;
+        // handlers registered with `on` need an event bus of their own
+        if script.registers_handlers {
+            return self.fork()?.eval_raw(contents, input);
+        }
+        let mut stack = self.snapshot.new_stack();
+        let data = nu_engine::eval_block::<WithoutDebug>(
+            &script.engine_state,
+            &mut stack,
+            &script.block,
+            input,
+        )?;
+
+        Ok(data)
+    }
+
+    /// Calls a function by the given name with its own stack.
+    /// Errs if the function doesn't exist
+    pub fn call_fn<S: AsRef<str>, I: IntoIterator<Item = A>, A: IntoArgument>(
+        &self,
+        name: S,
+        args: I,
+    ) -> CrateResult<PipelineData> {
+        if self.registers_handlers {
+            return self.fork()?.call_fn(name, args);
+        }
+        let engine_state = self.snapshot.engine_state();
+        let call = build_call(engine_state, name, args)?;
+        let mut stack = self.snapshot.new_stack();
+        let data = call.eval(engine_state, &mut stack)?;
+
+        Ok(data)
+    }
+
+    /// Returns if the given function exists in the context
+    pub fn has_fn<S: AsRef<str>>(&self, name: S) -> bool {
+        self.snapshot.has_fn(name)
+    }
+
+    /// Creates an isolated context for several evaluations that build on each other
+    pub fn fork(&self) -> CrateResult<Context> {
+        self.snapshot.fork()
+    }
+
+    /// Parses the script without changing the shared definitions
+    fn parse(&self, contents: String) -> CrateResult<ParsedScript> {
+        if let Some(script) = self.scripts.read().unwrap().get(&contents) {
+            return Ok(script.clone());
+        }
+        let engine_state = self.snapshot.engine_state();
+        let mut working_set = StateWorkingSet::new(engine_state);
+        let block = parse_in_working_set(
+            &mut working_set,
+            contents.clone(),
+            self.snapshot.protected(),
+        )?;
+
+        // new variables only live on the stack, so the shared state can evaluate
+        // the script as long as it doesn't reference any new blocks
+        if working_set.num_decls() == engine_state.num_decls()
+            && working_set.num_blocks() == engine_state.num_blocks()
+            && working_set.num_modules() == engine_state.num_modules()
+        {
+            return Ok(ParsedScript {
+                engine_state: Arc::clone(engine_state),
+                registers_handlers: self.snapshot.has_events()
+                    && registers_handlers(engine_state, &block),
+                block,
+            });
+        }
+        let first_new_block = engine_state.num_blocks();
+        let delta = working_set.render();
+        let mut engine_state = EngineState::clone(engine_state);
+        engine_state.merge_delta(delta)?;
+        let registers_handlers = self.snapshot.has_events()
+            && std::iter::once(block.as_ref())
+                .chain(
+                    (first_new_block..engine_state.num_blocks())
+                        .map(|id| engine_state.get_block(BlockId::new(id)).as_ref()),
+                )
+                .any(|block| registers_handlers(&engine_state, block));
+        let script = ParsedScript {
+            engine_state: Arc::new(engine_state),
+            block,
+            registers_handlers,
+        };
+
+        let mut scripts = self.scripts.write().unwrap();
+        if scripts.len() >= MAX_CACHED_SCRIPTS {
+            scripts.clear();
+        }
+        scripts.insert(contents, script.clone());
+
+        Ok(script)
+    }
+}
+
+/// Returns if the block calls `on`. Blocks without IR can't be evaluated at all
+fn registers_handlers(engine_state: &EngineState, block: &Block) -> bool {
+    let Some(ir_block) = &block.ir_block else {
+        return false;
+    };
+    ir_block
+        .instructions
+        .iter()
+        .any(|instruction| match instruction {
+            Instruction::Call { decl_id, .. } => {
+                let decl = engine_state.get_decl(*decl_id);
+                decl.name() == "on" && !decl.is_custom()
+            }
+            _ => false,
+        })
+}
+
+impl From<ContextSnapshot> for SharedContext {
+    fn from(snapshot: ContextSnapshot) -> Self {
+        Self::new(snapshot)
+    }
+}
+
+impl Context {
+    /// Turns the context into a context that can be shared between threads
+    pub fn into_shared(self) -> SharedContext {
+        SharedContext::new(self.snapshot())
+    }
+}
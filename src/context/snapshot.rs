@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use nu_protocol::engine::{EngineState, Stack};
+
+use crate::{error::CrateResult, events::EventBus, protection::ProtectedDecls};
+
+use super::Context;
+
+/// A frozen copy of a context that serves as template for isolated contexts.
+/// The definitions are shared between the snapshot and all of its forks
+/// and are only copied when a fork changes them
+#[derive(Clone)]
+pub struct ContextSnapshot {
+    engine_state: Arc<EngineState>,
+    stack: Arc<Stack>,
+    protected: ProtectedDecls,
+    events: EventBus,
+}
+
+impl ContextSnapshot {
+    /// Creates a new context from the snapshot.
+    /// Definitions, variables and environment changes of the new context
+    /// aren't visible to the snapshot or other forks
+    pub fn fork(&self) -> CrateResult<Context> {
+        let mut ctx = Context {
+            engine_state: Arc::clone(&self.engine_state),
+            stack: self.new_stack(),
+            protected: self.protected.clone(),
+            events: self.events.clone(),
+        };
+        ctx.fork_events();
+
+        Ok(ctx)
+    }
+
+    /// The definitions shared by all forks
+    pub(super) fn engine_state(&self) -> &Arc<EngineState> {
+        &self.engine_state
+    }
+
+    /// Creates a stack for an evaluation that doesn't change the snapshot
+    pub(super) fn new_stack(&self) -> Stack {
+        Stack::with_parent(Arc::clone(&self.stack))
+    }
+
+    /// The protected declarations of the snapshot
+    pub(super) fn protected(&self) -> &ProtectedDecls {
+        &self.protected
+    }
+
+    /// Whether the evaluations need their own event bus to keep their
+    /// handlers from leaking into other forks
+    pub(super) fn has_events(&self) -> bool {
+        self.events.is_enabled()
+    }
+
+    /// Returns if the given function exists in the snapshot
+    pub fn has_fn<S: AsRef<str>>(&self, name: S) -> bool {
+        self.engine_state
+            .find_decl(name.as_ref().as_bytes(), &[])
+            .is_some()
+    }
+}
+
+impl Context {
+    /// Takes a snapshot of the current state of the context.
+    /// Later changes to the context don't affect the snapshot
+    pub fn snapshot(&self) -> ContextSnapshot {
+        ContextSnapshot {
+            engine_state: Arc::clone(&self.engine_state),
+            stack: Arc::new(self.stack.clone()),
+            protected: self.protected.clone(),
+            events: self.events.clone(),
+        }
+    }
+
+    /// Creates an isolated copy of the context.
+    /// Forking is cheap as the definitions are shared until the fork changes them
+    pub fn fork(&self) -> CrateResult<Context> {
+        self.snapshot().fork()
+    }
+}
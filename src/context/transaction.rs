@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use nu_protocol::{PipelineData, Span, Value};
+
+use crate::{
+    NewEmpty,
+    error::{CrateError, CrateResult},
+};
+
+use super::Context;
+
+impl Context {
+    /// Runs the given function as a transaction.
+    /// If it fails the definitions, variables, environment and event handlers
+    /// of the context are reset to the state before the call
+    pub fn transaction<T, F>(&mut self, func: F) -> CrateResult<T>
+    where
+        F: FnOnce(&mut Context) -> CrateResult<T>,
+    {
+        let engine_state = Arc::clone(&self.engine_state);
+        let stack = self.stack.clone();
+        let handlers = self.events.saved_handlers();
+
+        let result = func(self);
+        if result.is_err() {
+            self.engine_state = engine_state;
+            self.stack = stack;
+            self.events.restore_handlers(handlers);
+        }
+
+        result
+    }
+
+    /// Evals nu script as string as transaction.
+    /// The output is collected so that errors raised while streaming
+    /// it roll back the changes of the script as well.
+    /// See [Context::transaction]
+    pub fn eval_transaction<S: ToString>(
+        &mut self,
+        contents: S,
+        input: PipelineData,
+    ) -> CrateResult<Value> {
+        self.transaction(|ctx| {
+            let pipeline = ctx.eval_raw(contents, input)?;
+            let value = match pipeline {
+                PipelineData::ListStream(..) => Value::list(
+                    ctx.iter_as::<Value>(pipeline)
+                        .collect::<CrateResult<Vec<_>>>()?,
+                    Span::empty(),
+                ),
+                pipeline => pipeline.into_value(Span::empty())?,
+            };
+
+            match value {
+                Value::Error { error, .. } => Err(CrateError::from(*error)),
+                value => Ok(value),
+            }
+        })
+    }
+}
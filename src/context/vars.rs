@@ -0,0 +1,61 @@
+use std::{collections::HashMap, sync::Arc};
+
+use nu_protocol::{Span, Value};
+
+use crate::{
+    IntoValue, NewEmpty,
+    error::{CrateError, CrateResult},
+    variable::find_var,
+};
+
+use super::Context;
+
+impl Context {
+    /// Changes the value of an existing variable in place.
+    /// Errs if the variable doesn't exist or isn't mutable
+    pub fn set_var<S: AsRef<str>, V: IntoValue>(&mut self, name: S, value: V) -> CrateResult<()> {
+        let name = name.as_ref();
+        let var_id = find_var(&self.engine_state, name)
+            .ok_or_else(|| CrateError::VariableNotFound(name.to_string()))?;
+
+        if !self.engine_state.get_var(var_id).mutable {
+            return Err(CrateError::ImmutableVariable(name.to_string()));
+        }
+        self.stack.add_var(var_id, value.into_value());
+
+        Ok(())
+    }
+
+    /// Removes the variable from the scope so following scripts can't reference it.
+    /// Returns the value of the removed variable
+    pub fn remove_var<S: AsRef<str>>(&mut self, name: S) -> Option<Value> {
+        let var_id = find_var(&self.engine_state, name.as_ref())?;
+        let value = self.stack.get_var(var_id, Span::empty()).ok();
+        self.stack.remove_var(var_id);
+
+        let engine_state = Arc::make_mut(&mut self.engine_state);
+        for (_, overlay) in engine_state.scope.overlays.iter_mut() {
+            overlay.vars.retain(|_, id| *id != var_id);
+        }
+
+        value
+    }
+
+    /// Returns the visible variables and their values
+    pub fn vars(&self) -> HashMap<String, Value> {
+        let mut vars = HashMap::new();
+
+        for overlay in self.engine_state.active_overlays(&[]) {
+            for (name, var_id) in &overlay.vars {
+                let name = String::from_utf8_lossy(name);
+                let name = name.trim_start_matches('$');
+
+                if let Ok(value) = self.stack.get_var(*var_id, Span::empty()) {
+                    vars.insert(name.to_string(), value);
+                }
+            }
+        }
+
+        vars
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+type EnvPredicate = dyn Fn(&str, &str) -> bool + Send + Sync;
+
+/// Selects the environment variables of the parent process
+/// that are inherited by [crate::ContextBuilder::add_parent_env_vars_filtered].
+/// Without any allow rule all variables are inherited.
+/// Deny rules and predicates always take precedence over allow rules
+#[derive(Clone, Default)]
+pub struct EnvFilter {
+    allowed: Vec<String>,
+    allowed_prefixes: Vec<String>,
+    denied: Vec<String>,
+    denied_prefixes: Vec<String>,
+    predicates: Vec<Arc<EnvPredicate>>,
+}
+
+impl EnvFilter {
+    /// Creates a filter that inherits all variables
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only inherits the given variables and those allowed by other allow rules
+    pub fn allow<I: IntoIterator<Item = S>, S: ToString>(mut self, names: I) -> Self {
+        self.allowed
+            .extend(names.into_iter().map(|n| n.to_string()));
+
+        self
+    }
+
+    /// Only inherits variables starting with the given prefix
+    /// and those allowed by other allow rules
+    pub fn allow_prefix<S: ToString>(mut self, prefix: S) -> Self {
+        self.allowed_prefixes.push(prefix.to_string());
+
+        self
+    }
+
+    /// Never inherits the given variables
+    pub fn deny<I: IntoIterator<Item = S>, S: ToString>(mut self, names: I) -> Self {
+        self.denied.extend(names.into_iter().map(|n| n.to_string()));
+
+        self
+    }
+
+    /// Never inherits variables starting with the given prefix
+    pub fn deny_prefix<S: ToString>(mut self, prefix: S) -> Self {
+        self.denied_prefixes.push(prefix.to_string());
+
+        self
+    }
+
+    /// Only inherits variables for which the predicate returns true.
+    /// The predicate is called with the name and value of the variable
+    pub fn filter<F: Fn(&str, &str) -> bool + Send + Sync + 'static>(
+        mut self,
+        predicate: F,
+    ) -> Self {
+        self.predicates.push(Arc::new(predicate));
+
+        self
+    }
+
+    /// Returns if the variable should be inherited
+    pub fn matches(&self, name: &str, value: &str) -> bool {
+        let allowed = (self.allowed.is_empty() && self.allowed_prefixes.is_empty())
+            || self.allowed.iter().any(|n| n == name)
+            || self.allowed_prefixes.iter().any(|p| name.starts_with(p));
+        let denied = self.denied.iter().any(|n| n == name)
+            || self.denied_prefixes.iter().any(|p| name.starts_with(p));
+
+        allowed && !denied && self.predicates.iter().all(|p| p(name, value))
+    }
+}
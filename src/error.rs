@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use miette::Diagnostic;
 use nu_protocol::{ParseError, ShellError};
 use thiserror::Error;
@@ -17,4 +19,20 @@ pub enum CrateError {
     #[error("Could not find the function {0}")]
     #[diagnostic()]
     FunctionNotFound(String),
+
+    #[error("The command group {0} is not available. Enable the `{1}` feature to use it")]
+    #[diagnostic()]
+    CommandGroupUnavailable(String, String),
+
+    #[error("The working directory {0:?} is not an absolute path to an existing directory")]
+    #[diagnostic()]
+    InvalidCwd(PathBuf),
+
+    #[error("Could not find the variable {0}")]
+    #[diagnostic()]
+    VariableNotFound(String),
+
+    #[error("The variable {0} is immutable and can't be changed")]
+    #[diagnostic()]
+    ImmutableVariable(String),
 }
@@ -0,0 +1,119 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        mpsc::{Receiver, Sender, channel},
+    },
+};
+
+use nu_protocol::{Value, engine::Closure};
+
+/// An event emitted by a script with `emit <name> <payload>`
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub name: String,
+    pub payload: Value,
+}
+
+type EventCallback = dyn Fn(&Event) + Send + Sync;
+
+#[derive(Clone)]
+enum Subscriber {
+    Callback(Arc<EventCallback>),
+    Channel(Sender<Event>),
+}
+
+#[derive(Clone, Default)]
+struct EventBusInner {
+    subscribers: HashMap<String, Vec<Subscriber>>,
+    handlers: HashMap<String, Vec<Closure>>,
+}
+
+/// Delivers events between scripts and the host.
+/// Clones share the same subscribers and handlers
+#[derive(Clone, Default)]
+pub(crate) struct EventBus {
+    inner: Arc<Mutex<EventBusInner>>,
+    enabled: bool,
+}
+
+impl EventBus {
+    /// Marks the bus as used by the `emit` and `on` commands
+    pub fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns a bus with a copy of the subscribers and handlers
+    /// that doesn't share new subscriptions with this one
+    pub fn fork(&self) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(self.lock().clone())),
+            enabled: self.enabled,
+        }
+    }
+
+    pub fn subscribe(&self, name: String, callback: Arc<EventCallback>) {
+        self.lock()
+            .subscribers
+            .entry(name)
+            .or_default()
+            .push(Subscriber::Callback(callback));
+    }
+
+    pub fn subscribe_channel(&self, name: String) -> Receiver<Event> {
+        let (tx, rx) = channel();
+        self.lock()
+            .subscribers
+            .entry(name)
+            .or_default()
+            .push(Subscriber::Channel(tx));
+
+        rx
+    }
+
+    /// Delivers the event to all host subscribers of the event
+    pub fn emit(&self, event: Event) {
+        let mut callbacks = Vec::new();
+
+        if let Some(subscribers) = self.lock().subscribers.get_mut(&event.name) {
+            // channels with dropped receivers are removed
+            subscribers.retain(|subscriber| match subscriber {
+                Subscriber::Callback(callback) => {
+                    callbacks.push(Arc::clone(callback));
+                    true
+                }
+                Subscriber::Channel(tx) => tx.send(event.clone()).is_ok(),
+            });
+        }
+        // callbacks are called without holding the lock so they can subscribe to events
+        for callback in callbacks {
+            callback(&event);
+        }
+    }
+
+    /// Registers a script closure that is called when the host triggers the event
+    pub fn add_handler(&self, name: String, closure: Closure) {
+        self.lock().handlers.entry(name).or_default().push(closure);
+    }
+
+    /// Returns all handlers registered by scripts so they can be restored later
+    pub fn saved_handlers(&self) -> HashMap<String, Vec<Closure>> {
+        self.lock().handlers.clone()
+    }
+
+    pub fn restore_handlers(&self, handlers: HashMap<String, Vec<Closure>>) {
+        self.lock().handlers = handlers;
+    }
+
+    pub fn handlers(&self, name: &str) -> Vec<Closure> {
+        self.lock().handlers.get(name).cloned().unwrap_or_default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, EventBusInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
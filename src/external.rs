@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+use nu_protocol::{PipelineData, Span, Value};
+
+/// An invocation of an external command like `^git status`
+pub struct ExternalCall {
+    /// The name or path of the program to run
+    pub program: String,
+    /// The arguments passed to the program as written in the script.
+    /// Unquoted arguments are globs that are expanded when the program is run
+    pub args: Vec<Value>,
+    /// The environment the program would be run with
+    pub env: HashMap<String, Value>,
+    /// The data piped into the program
+    pub input: PipelineData,
+    /// The span of the call in the script
+    pub span: Span,
+}
+
+/// Decides what happens with an intercepted external call
+pub enum ExternalAction {
+    /// Runs the given call. The call can be rewritten before
+    /// passing it back to run a different program or with other arguments
+    Run(ExternalCall),
+    /// Denies the call with the given reason
+    Deny(String),
+    /// Answers the call with synthetic output instead of spawning a process
+    Respond(PipelineData),
+}
+
+/// Handler for every external command that is invoked by scripts
+pub trait ExternalHandler: Send + Sync {
+    fn handle(&self, call: ExternalCall) -> ExternalAction;
+}
+
+impl<F: Fn(ExternalCall) -> ExternalAction + Send + Sync> ExternalHandler for F {
+    #[inline]
+    fn handle(&self, call: ExternalCall) -> ExternalAction {
+        self(call)
+    }
+}
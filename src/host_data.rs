@@ -0,0 +1,126 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+    sync::{Arc, OnceLock},
+};
+
+use nu_protocol::{
+    CustomValue, ShellError, Span, Value, VarId,
+    engine::{EngineState, StateWorkingSet},
+};
+
+use crate::utils::{NewEmpty, impl_unserializable};
+
+/// Name of the variable holding the host data. The name is removed from the scope
+/// right away so the variable is neither visible to scripts nor to `scope variables`
+const HOST_DATA_VAR: &[u8] = b"$embed-nu host data";
+
+/// The variable holding the host data is reserved as the first variable
+/// after the five variables every engine state starts with
+const HOST_DATA_VAR_ID: VarId = VarId::new(5);
+
+/// Typed data of the host application that can be accessed by commands
+#[derive(Clone, Default)]
+pub(crate) struct HostData {
+    values: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl HostData {
+    pub fn insert<T: Send + Sync + 'static>(&mut self, data: T) {
+        self.values.insert(TypeId::of::<T>(), Arc::new(data));
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|data| data.downcast_ref())
+    }
+}
+
+/// The slot in the engine state the host data is stored in when the context is built
+#[derive(Clone)]
+pub(crate) struct HostDataSlot(Arc<OnceLock<HostData>>);
+
+impl HostDataSlot {
+    /// Reserves the variable holding the host data in a new engine state
+    pub fn reserve(engine_state: &mut EngineState) -> Self {
+        let slot = Self(Arc::default());
+        let mut working_set = StateWorkingSet::new(engine_state);
+        let var_id = working_set.add_variable(
+            HOST_DATA_VAR.to_vec(),
+            Span::empty(),
+            nu_protocol::Type::Any,
+            false,
+        );
+        assert_eq!(var_id, HOST_DATA_VAR_ID, "the engine state isn't new");
+        working_set.last_overlay_mut().vars.remove(HOST_DATA_VAR);
+        working_set.set_variable_const_val(
+            var_id,
+            Value::custom(Box::new(HostDataValue(slot.clone())), Span::empty()),
+        );
+        let delta = working_set.render();
+        engine_state
+            .merge_delta(delta)
+            .expect("a delta with only a new variable can always be merged");
+
+        slot
+    }
+
+    pub fn fill(&self, data: HostData) {
+        let _ = self.0.set(data);
+    }
+}
+
+/// Returns the data of the given type that has been added with
+/// [crate::ContextBuilder::with_data]
+pub fn host_data<T: Send + Sync + 'static>(engine_state: &EngineState) -> Option<&T> {
+    if engine_state.num_vars() <= HOST_DATA_VAR_ID.get() {
+        return None;
+    }
+    match &engine_state.get_var(HOST_DATA_VAR_ID).const_val {
+        Some(Value::Custom { val, .. }) => val
+            .as_any()
+            .downcast_ref::<HostDataValue>()?
+            .0
+            .0
+            .get()?
+            .get(),
+        _ => None,
+    }
+}
+
+/// Wrapper to store the host data inside of the engine state
+#[derive(Clone)]
+struct HostDataValue(HostDataSlot);
+
+impl fmt::Debug for HostDataValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HostDataValue")
+    }
+}
+
+impl_unserializable!(HostDataValue);
+
+#[typetag::serde]
+impl CustomValue for HostDataValue {
+    fn clone_value(&self, span: Span) -> Value {
+        Value::custom(Box::new(self.clone()), span)
+    }
+
+    fn type_name(&self) -> String {
+        String::from("host data")
+    }
+
+    fn to_base_value(&self, span: Span) -> Result<Value, ShellError> {
+        Ok(Value::nothing(span))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
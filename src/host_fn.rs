@@ -0,0 +1,107 @@
+use nu_protocol::{FromValue, ShellError, Span, SyntaxShape, Type, Value};
+
+use crate::IntoValue;
+
+/// Converts the return value of a host function into a nu value
+pub trait IntoFnResult {
+    fn into_fn_result(self, span: Span) -> Result<Value, ShellError>;
+}
+
+impl<T: IntoValue, E: ToString> IntoFnResult for Result<T, E> {
+    fn into_fn_result(self, span: Span) -> Result<Value, ShellError> {
+        self.map(|val| val.into_value())
+            .map_err(|e| ShellError::GenericError {
+                error: String::from("Host function failed"),
+                msg: e.to_string(),
+                span: Some(span),
+                help: None,
+                inner: Vec::new(),
+            })
+    }
+}
+
+/// A rust closure that can be registered as a nu command.
+/// It's implemented for closures with up to six parameters that implement [FromValue].
+/// Parameters that accept `nothing` like `Option<T>` can be omitted by the caller
+/// as long as no parameter after them is required
+pub trait HostFn<Args>: Send + Sync + 'static {
+    /// The shape of each parameter and whether it's optional
+    fn parameters() -> Vec<(SyntaxShape, bool)>;
+
+    fn call(&self, args: Vec<Option<Value>>, span: Span) -> Result<Value, ShellError>;
+}
+
+/// The shape of a parameter derived from the type expected by its conversion.
+/// Parameters that accept `nothing` can also be passed `null`
+#[doc(hidden)]
+pub fn parameter_shape<T: FromValue>() -> SyntaxShape {
+    let shape = match T::expected_type() {
+        // floats are also converted from ints
+        Type::Float => SyntaxShape::Number,
+        ty => ty.to_shape(),
+    };
+
+    if shape != SyntaxShape::Any && accepts_nothing::<T>() {
+        SyntaxShape::OneOf(vec![shape, SyntaxShape::Nothing])
+    } else {
+        shape
+    }
+}
+
+/// Whether the parameter can be omitted because its conversion accepts `nothing`
+#[doc(hidden)]
+#[inline]
+pub fn accepts_nothing<T: FromValue>() -> bool {
+    T::from_value(Value::nothing(Span::unknown())).is_ok()
+}
+
+/// Marks the parameters that accept `nothing` as optional.
+/// The parser fills required parameters first, so only trailing parameters
+/// can be optional without changing the position of the arguments
+#[doc(hidden)]
+pub fn positional_parameters(params: Vec<(SyntaxShape, bool)>) -> Vec<(SyntaxShape, bool)> {
+    let num_required = params
+        .iter()
+        .rposition(|(_, optional)| !optional)
+        .map_or(0, |i| i + 1);
+
+    params
+        .into_iter()
+        .enumerate()
+        .map(|(i, (shape, optional))| (shape, optional && i >= num_required))
+        .collect()
+}
+
+macro_rules! impl_host_fn {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg: FromValue),*> HostFn<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + Send + Sync + 'static,
+            R: IntoFnResult,
+        {
+            fn parameters() -> Vec<(SyntaxShape, bool)> {
+                positional_parameters(vec![$((parameter_shape::<$arg>(), accepts_nothing::<$arg>())),*])
+            }
+
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn call(&self, args: Vec<Option<Value>>, span: Span) -> Result<Value, ShellError> {
+                let mut args = args.into_iter();
+                $(
+                    let $arg = $arg::from_value(
+                        args.next().flatten().unwrap_or_else(|| Value::nothing(span)),
+                    )?;
+                )*
+
+                self($($arg),*).into_fn_result(span)
+            }
+        }
+    };
+}
+
+impl_host_fn!();
+impl_host_fn!(A);
+impl_host_fn!(A, B);
+impl_host_fn!(A, B, C);
+impl_host_fn!(A, B, C, D);
+impl_host_fn!(A, B, C, D, E);
+impl_host_fn!(A, B, C, D, E, G);
@@ -0,0 +1,144 @@
+use std::{any::Any, fmt, sync::Arc};
+
+use nu_protocol::{CustomValue, FromValue, ShellError, Span, Type, Value};
+
+use crate::{
+    IntoValue,
+    utils::{NewEmpty, impl_unserializable},
+};
+
+/// A rust object that is passed to scripts as opaque value.
+/// Scripts can't look inside of the object except for the fields
+/// returned by [HostObject::get_field]
+pub trait HostObject: Send + Sync + 'static {
+    /// The type name shown by `describe`
+    fn type_name(&self) -> String;
+
+    /// Returns the field with the given name for cell paths like `$doc.title`
+    fn get_field(&self, _name: &str) -> Option<Value> {
+        None
+    }
+}
+
+/// A shared reference to a host object.
+/// It can be used as parameter of host functions to
+/// accept a host object passed by the script
+pub struct HostRef<T>(pub Arc<T>);
+
+impl<T> Clone for HostRef<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> std::ops::Deref for HostRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: HostObject> HostRef<T> {
+    pub fn new(object: T) -> Self {
+        Self(Arc::new(object))
+    }
+
+    /// Returns the host object stored in the value if it has the type `T`
+    pub fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Custom { val, .. } => val
+                .as_any()
+                .downcast_ref::<HostValue>()?
+                .any
+                .clone()
+                .downcast::<T>()
+                .ok()
+                .map(Self),
+            _ => None,
+        }
+    }
+}
+
+impl<T: HostObject> IntoValue for HostRef<T> {
+    fn into_value(self) -> Value {
+        let any: Arc<dyn Any + Send + Sync> = self.0.clone();
+        let object: Arc<dyn HostObject> = self.0;
+
+        Value::custom(Box::new(HostValue { object, any }), Span::empty())
+    }
+}
+
+impl<T: HostObject> FromValue for HostRef<T> {
+    fn from_value(value: Value) -> Result<Self, ShellError> {
+        Self::from_value(&value).ok_or_else(|| ShellError::CantConvert {
+            to_type: Self::expected_type().to_string(),
+            from_type: value.get_type().to_string(),
+            span: value.span(),
+            help: None,
+        })
+    }
+
+    #[inline]
+    fn expected_type() -> Type {
+        Type::Custom(std::any::type_name::<T>().into())
+    }
+}
+
+/// The custom value wrapping a host object.
+/// The object is freed when the last value referencing it is dropped
+#[derive(Clone)]
+struct HostValue {
+    object: Arc<dyn HostObject>,
+    any: Arc<dyn Any + Send + Sync>,
+}
+
+impl fmt::Debug for HostValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HostValue({})", self.object.type_name())
+    }
+}
+
+impl_unserializable!(HostValue);
+
+#[typetag::serde]
+impl CustomValue for HostValue {
+    fn clone_value(&self, span: Span) -> Value {
+        Value::custom(Box::new(self.clone()), span)
+    }
+
+    fn type_name(&self) -> String {
+        self.object.type_name()
+    }
+
+    fn to_base_value(&self, span: Span) -> Result<Value, ShellError> {
+        Ok(Value::string(
+            format!("<{}>", self.object.type_name()),
+            span,
+        ))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn follow_path_string(
+        &self,
+        self_span: Span,
+        column_name: String,
+        path_span: Span,
+    ) -> Result<Value, ShellError> {
+        match self.object.get_field(&column_name) {
+            Some(value) => Ok(value.with_span(path_span)),
+            None => Err(ShellError::CantFindColumn {
+                col_name: column_name,
+                span: Some(path_span),
+                src_span: self_span,
+            }),
+        }
+    }
+}
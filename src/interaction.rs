@@ -0,0 +1,68 @@
+/// A request for a line of text by `input "prompt"`
+pub struct TextPrompt {
+    /// The prompt shown to the user
+    pub message: String,
+    /// The value used when the user answers with an empty line
+    pub default: Option<String>,
+    /// Whether the typed text should be hidden like for passwords
+    pub secret: bool,
+}
+
+/// A request to choose from a list of options by `input list`
+pub struct SelectPrompt {
+    /// The prompt shown to the user
+    pub message: String,
+    /// The options as they should be displayed
+    pub options: Vec<String>,
+    /// Whether several options can be selected
+    pub multiple: bool,
+}
+
+/// A request for a yes or no answer by `confirm "prompt"`
+pub struct ConfirmPrompt {
+    /// The prompt shown to the user
+    pub message: String,
+}
+
+/// The answer of the host to a prompt
+pub enum PromptResponse<T> {
+    /// The answer of the user
+    Value(T),
+    /// The user dismissed the prompt
+    Cancelled,
+    /// The prompt can't be shown with the given reason
+    Unavailable(String),
+}
+
+/// Provides the user interaction for prompts of scripts.
+/// Every prompt that isn't implemented fails instead of reading from stdin
+pub trait InteractionProvider: Send + Sync {
+    /// Asks for a line of text
+    fn input(&self, _prompt: TextPrompt) -> PromptResponse<String> {
+        non_interactive()
+    }
+
+    /// Asks to select one or more options.
+    /// Returns the indices of the selected options
+    fn select(&self, _prompt: SelectPrompt) -> PromptResponse<Vec<usize>> {
+        non_interactive()
+    }
+
+    /// Asks for a confirmation
+    fn confirm(&self, _prompt: ConfirmPrompt) -> PromptResponse<bool> {
+        non_interactive()
+    }
+}
+
+/// A provider for hosts without any user interface.
+/// All prompts fail with an error.
+/// This is the provider of contexts that don't configure another one
+pub struct NonInteractive;
+
+impl InteractionProvider for NonInteractive {}
+
+fn non_interactive<T>() -> PromptResponse<T> {
+    PromptResponse::Unavailable(String::from(
+        "the host application doesn't support interactive prompts",
+    ))
+}
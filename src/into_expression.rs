@@ -1,12 +1,25 @@
 use nu_protocol::{
-    Id, Span, Value,
+    Id, Span, Value, VarId,
     ast::{Expr, Expression, ListItem, RecordItem},
 };
 
 use crate::{IntoValue, NewEmpty};
 
+/// The values captured by closures, by the variables they are bound to
+pub type Captures = Vec<(VarId, Value)>;
+
 pub trait IntoExpression {
     fn into_expression(self) -> Expression;
+
+    /// Converts into an expression together with the values captured by its closures.
+    /// Expressions only reference the block of a closure, so the captured values
+    /// need to be on the stack the expression is evaluated with
+    fn into_expression_with_captures(self) -> (Expression, Captures)
+    where
+        Self: Sized,
+    {
+        (self.into_expression(), Vec::new())
+    }
 }
 
 pub trait ValueIntoExpression {
@@ -19,6 +32,23 @@ impl<V: IntoValue> IntoExpression for V {
     fn into_expression(self) -> Expression {
         self.into_value().into_expression()
     }
+
+    fn into_expression_with_captures(self) -> (Expression, Captures) {
+        let value = self.into_value();
+        let mut captures = Vec::new();
+        collect_captures(&value, &mut captures);
+
+        (value.into_expression(), captures)
+    }
+}
+
+fn collect_captures(value: &Value, captures: &mut Captures) {
+    match value {
+        Value::Closure { val, .. } => captures.extend(val.captures.iter().cloned()),
+        Value::List { vals, .. } => vals.iter().for_each(|v| collect_captures(v, captures)),
+        Value::Record { val, .. } => val.values().for_each(|v| collect_captures(v, captures)),
+        _ => {}
+    }
 }
 
 impl ValueIntoExpression for Value {
@@ -40,9 +70,10 @@ impl ValueIntoExpression for Value {
             Value::Int { val, .. } => Expr::Int(val),
             Value::Float { val, .. } => Expr::Float(val),
             Value::Filesize { val, .. } => Expr::Int(val.into()),
-            Value::Duration { val, .. } => Expr::Int(val.into()),
+            Value::Duration { val, .. } => Expr::Int(val),
             Value::Date { val, .. } => Expr::DateTime(val),
             Value::String { val, .. } => Expr::String(val),
+            Value::Glob { val, no_expand, .. } => Expr::GlobPattern(val, no_expand),
             Value::Record { val, .. } => {
                 let entries = val
                     .iter()
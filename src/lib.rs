@@ -1,20 +1,55 @@
 #![doc=include_str!("../README.md")]
 pub(crate) mod argument;
+pub(crate) mod callback;
 pub mod commands;
 pub(crate) mod context;
+pub(crate) mod env_filter;
 pub(crate) mod error;
+pub(crate) mod events;
+pub(crate) mod external;
+pub(crate) mod host_data;
+pub(crate) mod host_fn;
+pub(crate) mod host_object;
+pub(crate) mod interaction;
 pub(crate) mod into_expression;
 pub(crate) mod into_value;
+pub(crate) mod module;
+pub(crate) mod protection;
+pub(crate) mod stream;
+pub(crate) mod struct_command;
 pub(crate) mod utils;
+pub(crate) mod value_iter;
+pub(crate) mod variable;
 
 pub use argument::{Argument, IntoArgument};
-pub use context::{CommandGroupConfig, Context, ContextBuilder};
+pub use callback::CallbackFn;
+#[cfg(feature = "async")]
+pub use context::ValueStream;
+pub use context::{
+    CallMode, CommandGroupConfig, Context, ContextBuilder, ContextSnapshot, EnvDiff, SharedContext,
+};
+pub use embed_nu_derive::NuCommand;
+pub use env_filter::EnvFilter;
+pub use events::Event;
+pub use external::{ExternalAction, ExternalCall, ExternalHandler};
+pub use host_data::host_data;
+pub use host_fn::{HostFn, IntoFnResult, accepts_nothing, parameter_shape, positional_parameters};
+pub use host_object::{HostObject, HostRef};
+pub use interaction::{
+    ConfirmPrompt, InteractionProvider, NonInteractive, PromptResponse, SelectPrompt, TextPrompt,
+};
 pub use into_expression::*;
 pub use into_value::*;
+pub use module::ModuleBuilder;
 pub use nu_engine::{self, CallExt};
 pub use nu_parser;
 pub use nu_protocol::{self, PipelineData, Value};
+pub use protection::ShadowPolicy;
 pub use rusty_value;
+pub use stream::{byte_stream, list_stream};
+pub use struct_command::{CommandContext, NuCommand, NuCommandArgs, command_category, rest_shape};
 pub use utils::NewEmpty;
+pub use value_iter::ValueIter;
+pub use variable::{VarKind, VarOptions};
 
 pub type Error = error::CrateError;
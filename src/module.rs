@@ -0,0 +1,110 @@
+use nu_protocol::{
+    Module, Span, Value,
+    engine::{Command, EngineState, StateWorkingSet},
+};
+
+use crate::{
+    IntoValue,
+    commands::FnCommand,
+    error::{CrateError, CrateResult},
+    host_fn::HostFn,
+    utils::NewEmpty,
+};
+
+/// Builder for a nu module with commands and constants provided by the host.
+/// Scripts can import the module with `use`, `hide` and `overlay use` like any other module
+pub struct ModuleBuilder {
+    name: String,
+    commands: Vec<Box<dyn Command>>,
+    constants: Vec<(String, Value)>,
+    sources: Vec<String>,
+}
+
+impl ModuleBuilder {
+    /// Creates a new module with the given name
+    pub fn new<S: ToString>(name: S) -> Self {
+        Self {
+            name: name.to_string(),
+            commands: Vec::new(),
+            constants: Vec::new(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Exports a command from the module.
+    /// Commands named `config get` are called as `<module> config get` after `use <module>`
+    pub fn add_command<C: Command + 'static>(mut self, command: C) -> Self {
+        self.commands.push(Box::new(command));
+
+        self
+    }
+
+    /// Exports a rust closure as command from the module
+    /// See [crate::ContextBuilder::add_fn]
+    pub fn add_fn<S: ToString, Args, F: HostFn<Args>>(self, name: S, func: F) -> Self {
+        self.add_command(FnCommand::new(name.to_string(), func))
+    }
+
+    /// Exports a constant from the module
+    pub fn add_const<S: ToString, V: IntoValue>(mut self, name: S, value: V) -> Self {
+        self.constants.push((name.to_string(), value.into_value()));
+
+        self
+    }
+
+    /// Adds nu source to the module.
+    /// Definitions need to be exported with `export def` to be visible to scripts
+    pub fn add_source<S: ToString>(mut self, source: S) -> Self {
+        self.sources.push(source.to_string());
+
+        self
+    }
+
+    /// Registers the module on the engine state
+    pub(crate) fn register(self, engine_state: &mut EngineState) -> CrateResult<()> {
+        let mut working_set = StateWorkingSet::new(engine_state);
+        let source = self.sources.join("\n");
+        let file_id = working_set.add_file(format!("{} module", self.name), source.as_bytes());
+        let span = working_set.get_span_for_file(file_id);
+        let (_, mut module, comments) =
+            nu_parser::parse_module_block(&mut working_set, span, self.name.as_bytes());
+
+        if !working_set.parse_errors.is_empty() {
+            return Err(CrateError::NuParseErrors(working_set.parse_errors));
+        }
+        add_exports(&mut working_set, &mut module, self.commands, self.constants);
+        working_set.add_module(&self.name, module, comments);
+        let delta = working_set.render();
+        engine_state.merge_delta(delta)?;
+
+        Ok(())
+    }
+}
+
+/// Adds the commands and constants to the module without
+/// making them visible outside of it
+fn add_exports(
+    working_set: &mut StateWorkingSet,
+    module: &mut Module,
+    commands: Vec<Box<dyn Command>>,
+    constants: Vec<(String, Value)>,
+) {
+    working_set.enter_scope();
+
+    for command in commands {
+        let name = command.name().as_bytes().to_vec();
+        let decl_id = working_set.add_decl(command);
+        module.add_decl(name, decl_id);
+    }
+    for (name, value) in constants {
+        let var_id = working_set.add_variable(
+            name.clone().into_bytes(),
+            Span::empty(),
+            value.get_type(),
+            false,
+        );
+        working_set.set_variable_const_val(var_id, value);
+        module.add_variable(name.into_bytes(), var_id);
+    }
+    working_set.exit_scope();
+}
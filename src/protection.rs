@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use nu_protocol::{
+    DeclId, ModuleId, ParseError, Span,
+    ast::{Block, Expr},
+    engine::{EngineState, StateWorkingSet},
+};
+
+/// Controls whether scripts may define declarations with the name of a protected command
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShadowPolicy {
+    /// The name can't be used by any `def`, `alias` or `extern` and the command can't be hidden
+    Forbid,
+    /// The name can be reused inside of module definitions as long as the
+    /// protected command stays visible in the global scope
+    AllowInModules,
+}
+
+/// The declarations scripts are not allowed to shadow
+#[derive(Clone, Default)]
+pub(crate) struct ProtectedDecls {
+    policies: HashMap<Vec<u8>, ShadowPolicy>,
+}
+
+impl ProtectedDecls {
+    pub fn insert(&mut self, name: Vec<u8>, policy: ShadowPolicy) {
+        self.policies.insert(name, policy);
+    }
+
+    /// Returns the violations of the parsed but not yet merged changes in the working set
+    pub fn check(
+        &self,
+        engine_state: &EngineState,
+        working_set: &StateWorkingSet,
+        fallback_span: Span,
+    ) -> Vec<ParseError> {
+        let mut errors = Vec::new();
+        let module_spans = (engine_state.num_modules()..working_set.num_modules())
+            .filter_map(|id| working_set.get_module(ModuleId::new(id)).span)
+            .collect::<Vec<_>>();
+
+        for (name, policy) in &self.policies {
+            let display_name = String::from_utf8_lossy(name);
+            let num_errors = errors.len();
+
+            for id in engine_state.num_decls()..working_set.num_decls() {
+                let decl_id = DeclId::new(id);
+
+                if working_set.get_decl(decl_id).name().as_bytes() != name.as_slice() {
+                    continue;
+                }
+                let span = decl_span(working_set, decl_id);
+                let in_module = span.is_some_and(|span| {
+                    module_spans
+                        .iter()
+                        .any(|module_span| module_span.contains_span(span))
+                });
+
+                if *policy == ShadowPolicy::Forbid || !in_module {
+                    errors.push(shadow_error(&display_name, span.unwrap_or(fallback_span)));
+                }
+            }
+            // hiding a permanent declaration is only recorded in the overlay of the working set
+            let visible = working_set
+                .find_decl(name)
+                .filter(|id| working_set.last_overlay().visibility.is_decl_id_visible(id));
+
+            if errors.len() == num_errors && visible != engine_state.find_decl(name, &[]) {
+                let span = visible
+                    .and_then(|decl_id| decl_span(working_set, decl_id))
+                    .unwrap_or(fallback_span);
+                errors.push(shadow_error(&display_name, span));
+            }
+        }
+
+        errors
+    }
+
+    /// Returns the violations of a block that wasn't parsed by the context.
+    /// Its declarations don't exist in the engine state, so the calls
+    /// declaring or hiding commands are checked instead
+    pub fn check_block(&self, engine_state: &EngineState, block: &Block) -> Vec<ParseError> {
+        let mut errors = Vec::new();
+        self.check_calls(engine_state, block, false, &mut errors);
+
+        errors
+    }
+
+    fn check_calls(
+        &self,
+        engine_state: &EngineState,
+        block: &Block,
+        in_module: bool,
+        errors: &mut Vec<ParseError>,
+    ) {
+        for element in block.pipelines.iter().flat_map(|p| &p.elements) {
+            let Expr::Call(call) = &element.expr.expr else {
+                continue;
+            };
+            if call.decl_id.get() >= engine_state.num_decls() {
+                continue;
+            }
+            let keyword = engine_state.get_decl(call.decl_id).name();
+            let declared = call
+                .positional_nth(0)
+                .filter(|_| DECLARING_KEYWORDS.contains(&keyword))
+                .and_then(|arg| Some((arg.as_string()?, arg.span)));
+
+            if let Some((name, span)) = declared {
+                let violates = match self.policies.get(name.as_bytes()) {
+                    Some(ShadowPolicy::Forbid) => true,
+                    Some(ShadowPolicy::AllowInModules) => !in_module,
+                    None => false,
+                };
+                if violates {
+                    errors.push(shadow_error(&name, span));
+                }
+            }
+            let in_module = in_module || matches!(keyword, "module" | "export module");
+
+            for arg in call.positional_iter() {
+                if let Expr::Block(block_id) | Expr::Closure(block_id) = arg.expr
+                    && block_id.get() < engine_state.num_blocks()
+                {
+                    let block = engine_state.get_block(block_id);
+                    self.check_calls(engine_state, block, in_module, errors);
+                }
+            }
+        }
+    }
+}
+
+/// The keywords whose first argument is the name of a command they declare or hide
+const DECLARING_KEYWORDS: &[&str] = &[
+    "def",
+    "export def",
+    "alias",
+    "export alias",
+    "extern",
+    "export extern",
+    "hide",
+];
+
+fn decl_span(working_set: &StateWorkingSet, decl_id: DeclId) -> Option<Span> {
+    let decl = working_set.get_decl(decl_id);
+
+    decl.block_id()
+        .and_then(|block_id| working_set.get_block(block_id).span)
+        .or_else(|| decl.as_alias().map(|alias| alias.wrapped_call.span))
+}
+
+fn shadow_error(name: &str, span: Span) -> ParseError {
+    ParseError::LabeledError(
+        format!("The command `{name}` is protected"),
+        String::from("protected commands can't be redefined or hidden"),
+        span,
+    )
+}
@@ -0,0 +1,35 @@
+use std::io::Read;
+
+use nu_protocol::{ByteStream, ByteStreamType, ListStream, PipelineData, Signals, Span};
+
+use crate::IntoValue;
+
+/// Creates a lazy list stream from the given iterator.
+/// The stream stops when the signals are interrupted
+pub fn list_stream<I, V>(iter: I, span: Span, signals: Signals) -> PipelineData
+where
+    I: IntoIterator<Item = V>,
+    I::IntoIter: Send + 'static,
+    V: IntoValue,
+{
+    let stream = ListStream::new(
+        iter.into_iter()
+            .map(move |v| v.into_value().with_span(span)),
+        span,
+        signals,
+    );
+
+    PipelineData::ListStream(stream, None)
+}
+
+/// Creates a lazy byte stream from the given reader.
+/// The stream stops when the signals are interrupted
+pub fn byte_stream<R: Read + Send + 'static>(
+    reader: R,
+    span: Span,
+    signals: Signals,
+) -> PipelineData {
+    let stream = ByteStream::read(reader, span, signals, ByteStreamType::Unknown);
+
+    PipelineData::ByteStream(stream, None)
+}
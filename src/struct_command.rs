@@ -0,0 +1,110 @@
+use std::io::Read;
+
+use nu_protocol::{
+    Category, PipelineData, ShellError, Signature, Span, SyntaxShape,
+    engine::{Call, EngineState, Stack},
+};
+
+use crate::{
+    IntoValue,
+    stream::{byte_stream, list_stream},
+};
+
+/// The engine state passed to [NuCommand::run]
+pub struct CommandContext<'a> {
+    pub engine_state: &'a EngineState,
+    pub stack: &'a mut Stack,
+    /// The span of the command call
+    pub head: Span,
+}
+
+impl CommandContext<'_> {
+    /// Returns the host data of the given type
+    /// that has been added with [crate::ContextBuilder::with_data]
+    pub fn data<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        crate::host_data::host_data(self.engine_state)
+    }
+
+    /// Creates a lazy stream from the given iterator to return from the command
+    /// The stream stops when the evaluation is interrupted
+    pub fn stream_iter<I, V>(&self, iter: I) -> PipelineData
+    where
+        I: IntoIterator<Item = V>,
+        I::IntoIter: Send + 'static,
+        V: IntoValue,
+    {
+        list_stream(iter, self.head, self.engine_state.signals().clone())
+    }
+
+    /// Creates a lazy byte stream from the given reader to return from the command
+    /// The stream stops when the evaluation is interrupted
+    pub fn stream_reader<R: Read + Send + 'static>(&self, reader: R) -> PipelineData {
+        byte_stream(reader, self.head, self.engine_state.signals().clone())
+    }
+}
+
+/// Describes the arguments of a command declared as a struct.
+/// This trait is implemented with `#[derive(NuCommand)]`
+pub trait NuCommandArgs: Sized {
+    fn name() -> &'static str;
+
+    fn description() -> &'static str;
+
+    fn signature() -> Signature;
+
+    /// Parses the arguments of the call into the struct
+    fn from_call(
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+    ) -> Result<Self, ShellError>;
+}
+
+/// A command declared as a struct with its arguments as fields.
+/// It can be registered with [crate::ContextBuilder::add_nu_command]
+pub trait NuCommand: NuCommandArgs + Send + Sync + 'static {
+    fn run(self, ctx: CommandContext, input: PipelineData) -> Result<PipelineData, ShellError>;
+}
+
+/// Returns the category with the given name
+/// Unknown names are returned as a custom category
+#[doc(hidden)]
+pub fn command_category(name: &str) -> Category {
+    match name.to_lowercase().as_str() {
+        "bits" => Category::Bits,
+        "bytes" => Category::Bytes,
+        "chart" => Category::Chart,
+        "conversions" => Category::Conversions,
+        "core" => Category::Core,
+        "database" => Category::Database,
+        "date" => Category::Date,
+        "debug" => Category::Debug,
+        "default" => Category::Default,
+        "env" => Category::Env,
+        "experimental" => Category::Experimental,
+        "filesystem" => Category::FileSystem,
+        "filters" => Category::Filters,
+        "formats" => Category::Formats,
+        "generators" => Category::Generators,
+        "hash" => Category::Hash,
+        "math" => Category::Math,
+        "misc" => Category::Misc,
+        "network" => Category::Network,
+        "path" => Category::Path,
+        "platform" => Category::Platform,
+        "random" => Category::Random,
+        "strings" => Category::Strings,
+        "system" => Category::System,
+        "viewers" => Category::Viewers,
+        _ => Category::Custom(name.to_string()),
+    }
+}
+
+/// Returns the shape of a single item of a rest parameter
+#[doc(hidden)]
+pub fn rest_shape(shape: SyntaxShape) -> SyntaxShape {
+    match shape {
+        SyntaxShape::List(item) => *item,
+        shape => shape,
+    }
+}
@@ -6,7 +6,10 @@ use nu_protocol::{
     engine::{EngineState, StateWorkingSet},
 };
 
-use crate::error::{CrateError, CrateResult};
+use crate::{
+    error::{CrateError, CrateResult},
+    protection::ProtectedDecls,
+};
 
 pub trait NewEmpty {
     fn empty() -> Self;
@@ -19,16 +22,67 @@ impl NewEmpty for Span {
     }
 }
 
-pub fn parse_nu_script(engine_state: &mut EngineState, contents: String) -> CrateResult<Block> {
+pub fn parse_nu_script(
+    engine_state: &mut EngineState,
+    contents: String,
+    protected: &ProtectedDecls,
+) -> CrateResult<Block> {
     let mut working_set = StateWorkingSet::new(engine_state);
-    let block = nu_parser::parse(&mut working_set, None, &contents.into_bytes(), false);
+    let block = parse_in_working_set(&mut working_set, contents, protected)?;
+    let delta = working_set.render();
+    engine_state.merge_delta(delta)?;
+
+    Ok(block.as_ref().clone())
+}
+
+/// Parses the script into the working set without merging the changes.
+/// Errs if the script is invalid or violates the protection of declarations
+pub fn parse_in_working_set(
+    working_set: &mut StateWorkingSet,
+    contents: String,
+    protected: &ProtectedDecls,
+) -> CrateResult<Arc<Block>> {
+    let block = nu_parser::parse(working_set, None, &contents.into_bytes(), false);
 
     if working_set.parse_errors.is_empty() {
-        let delta = working_set.render();
-        engine_state.merge_delta(delta)?;
+        let script_span = block.span.unwrap_or_else(Span::unknown);
+        let errors = protected.check(working_set.permanent_state, working_set, script_span);
+        working_set.parse_errors.extend(errors);
+    }
 
-        Ok(block.as_ref().clone())
+    if working_set.parse_errors.is_empty() {
+        Ok(block)
     } else {
-        Err(CrateError::NuParseErrors(working_set.parse_errors))
+        Err(CrateError::NuParseErrors(std::mem::take(
+            &mut working_set.parse_errors,
+        )))
     }
 }
+
+/// Implements the serde traits for custom values that only live inside of the
+/// process. Both serializing and deserializing always fail
+macro_rules! impl_unserializable {
+    ($ty:ty) => {
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom(concat!(
+                    stringify!($ty),
+                    " can't be serialized"
+                )))
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(
+                _deserializer: D,
+            ) -> Result<Self, D::Error> {
+                Err(serde::de::Error::custom(concat!(
+                    stringify!($ty),
+                    " can't be deserialized"
+                )))
+            }
+        }
+    };
+}
+
+pub(crate) use impl_unserializable;
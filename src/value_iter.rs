@@ -0,0 +1,34 @@
+use std::marker::PhantomData;
+
+use nu_protocol::{FromValue, PipelineIterator, Value};
+
+use crate::error::{CrateError, CrateResult};
+
+/// Iterator that lazily pulls the values of a pipeline and converts them into `T`.
+/// Errors raised by the script while streaming are returned as `Err` items
+pub struct ValueIter<T> {
+    inner: PipelineIterator,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ValueIter<T> {
+    pub(crate) fn new(inner: PipelineIterator) -> Self {
+        Self {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: FromValue> Iterator for ValueIter<T> {
+    type Item = CrateResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = match self.inner.next()? {
+            Value::Error { error, .. } => Err(CrateError::from(*error)),
+            value => T::from_value(value).map_err(CrateError::from),
+        };
+
+        Some(item)
+    }
+}
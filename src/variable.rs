@@ -0,0 +1,121 @@
+use nu_protocol::{
+    Span, Type, Value, VarId,
+    engine::{EngineState, Stack, StateWorkingSet},
+};
+
+use crate::{error::CrateResult, utils::NewEmpty};
+
+/// How a variable added by the host can be used by scripts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VarKind {
+    /// A variable declared like `let`
+    #[default]
+    Immutable,
+    /// A variable declared like `mut` that scripts can reassign
+    Mutable,
+    /// A variable declared like `const` that can be used in
+    /// constant expressions like `source $dir/file.nu` at parse time
+    Const,
+}
+
+/// The options of a variable added by the host
+#[derive(Clone, Debug, Default)]
+pub struct VarOptions {
+    kind: VarKind,
+    ty: Option<Type>,
+}
+
+impl VarOptions {
+    /// An immutable variable like `let`
+    pub fn immutable() -> Self {
+        Self::default()
+    }
+
+    /// A mutable variable like `mut`
+    pub fn mutable() -> Self {
+        Self {
+            kind: VarKind::Mutable,
+            ty: None,
+        }
+    }
+
+    /// A constant like `const`
+    pub fn constant() -> Self {
+        Self {
+            kind: VarKind::Const,
+            ty: None,
+        }
+    }
+
+    /// Declares the type of the variable for the type checks of the parser.
+    /// Without it the type is inferred from the value
+    pub fn with_type(mut self, ty: Type) -> Self {
+        self.ty = Some(ty);
+
+        self
+    }
+
+    pub fn kind(&self) -> VarKind {
+        self.kind
+    }
+}
+
+/// Declares the variable in the engine state and sets its value on the stack
+pub(crate) fn register_var(
+    engine_state: &mut EngineState,
+    stack: &mut Stack,
+    name: String,
+    value: Value,
+    options: VarOptions,
+) -> CrateResult<VarId> {
+    let inferred = options.ty.is_none();
+    let ty = options.ty.unwrap_or_else(|| value.get_type());
+
+    if let Some(old_id) = find_var(engine_state, &name) {
+        let old = engine_state.get_var(old_id);
+
+        // definitions referencing the variable keep its id, so a variable of the same kind
+        // is updated in place. The value of a constant was already used by the parser
+        if options.kind != VarKind::Const
+            && old.const_val.is_none()
+            && old.mutable == (options.kind == VarKind::Mutable)
+            && (old.ty == ty || inferred)
+        {
+            stack.add_var(old_id, value.clone());
+
+            if old.ty == ty {
+                return Ok(old_id);
+            }
+        }
+        // the old value stays on the stack for the definitions referencing it
+        // and scripts parsed from now on use the new declaration
+    }
+    let mut working_set = StateWorkingSet::new(engine_state);
+
+    let var_id = working_set.add_variable(
+        name.into_bytes(),
+        Span::empty(),
+        ty,
+        options.kind == VarKind::Mutable,
+    );
+    if options.kind == VarKind::Const {
+        working_set.set_variable_const_val(var_id, value.clone());
+    }
+    stack.add_var(var_id, value);
+    let delta = working_set.render();
+    engine_state.merge_delta(delta)?;
+
+    Ok(var_id)
+}
+
+/// Returns the id of the visible variable with the given name
+pub(crate) fn find_var(engine_state: &EngineState, name: &str) -> Option<VarId> {
+    let dollar_name = format!("${name}");
+
+    engine_state.active_overlays(&[]).find_map(|o| {
+        o.vars
+            .get(dollar_name.as_bytes())
+            .or(o.vars.get(name.as_bytes()))
+            .copied()
+    })
+}
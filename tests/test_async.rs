@@ -0,0 +1,101 @@
+#![cfg(feature = "async")]
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use embed_nu::{CommandGroupConfig, Context, PipelineData};
+use futures_util::StreamExt;
+
+#[tokio::test]
+async fn it_evaluates_scripts_asynchronously() {
+    let mut ctx = get_context(Arc::default());
+    let value = ctx
+        .eval_raw_async(r#"1..10 | math sum"#, PipelineData::empty())
+        .await
+        .unwrap();
+    assert_eq!(value.as_int().unwrap(), 55);
+}
+
+#[tokio::test]
+async fn it_keeps_definitions_of_async_evaluations() {
+    let mut ctx = get_context(Arc::default());
+    ctx.eval_raw_async(r#"def double [x: int] { $x * 2 }"#, PipelineData::empty())
+        .await
+        .unwrap();
+    let value = ctx.call_fn_async("double", [21]).await.unwrap();
+    assert_eq!(value.as_int().unwrap(), 42);
+}
+
+#[tokio::test]
+async fn it_streams_values_asynchronously() {
+    let ctx = get_context(Arc::default());
+    let values = ctx
+        .eval_stream_async(r#"1.. | each {|x| $x * 2 }"#, PipelineData::empty())
+        .take(3)
+        .map(|v| v.unwrap().as_int().unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(values, vec![2, 4, 6]);
+}
+
+#[tokio::test]
+async fn it_interrupts_evaluations_when_dropped() {
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let mut ctx = get_context(Arc::clone(&ticks));
+    let result = tokio::time::timeout(
+        Duration::from_millis(100),
+        ctx.eval_raw_async(r#"loop { tick }"#, PipelineData::empty()),
+    )
+    .await;
+    assert!(result.is_err());
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let stopped_at = ticks.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(stopped_at > 0);
+    assert_eq!(ticks.load(Ordering::SeqCst), stopped_at);
+}
+
+#[tokio::test]
+async fn it_interrupts_scripts_without_host_functions() {
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&ticks);
+    let mut ctx = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .with_events()
+        .unwrap()
+        .build()
+        .unwrap();
+    ctx.subscribe("tick", move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    let result = tokio::time::timeout(
+        Duration::from_millis(100),
+        ctx.eval_raw_async(r#"loop { emit tick 1 }"#, PipelineData::empty()),
+    )
+    .await;
+    assert!(result.is_err());
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let stopped_at = ticks.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(stopped_at > 0);
+    assert_eq!(ticks.load(Ordering::SeqCst), stopped_at);
+}
+
+fn get_context(ticks: Arc<AtomicUsize>) -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_fn("tick", move || {
+            Ok::<_, String>(ticks.fetch_add(1, Ordering::SeqCst) as i64)
+        })
+        .unwrap()
+        .build()
+        .unwrap()
+}
@@ -0,0 +1,87 @@
+use embed_nu::{CommandGroupConfig, Context, PipelineData};
+use nu_protocol::Config;
+
+#[cfg(feature = "bit")]
+#[test]
+fn it_binds_bit_commands() {
+    let mut ctx = get_context();
+    let pipeline = ctx
+        .eval_raw(r#"2 | bits shl 2 | bits and 12"#, PipelineData::empty())
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(output, String::from("8"));
+}
+
+// commands of disabled groups are missing by design
+#[cfg(all(
+    feature = "core",
+    feature = "debug",
+    feature = "filter",
+    feature = "chart",
+    feature = "misc",
+    feature = "path",
+    feature = "system",
+    feature = "string",
+    feature = "bit",
+    feature = "byte",
+    feature = "file-system",
+    feature = "platform",
+    feature = "date",
+    feature = "shell",
+    feature = "format",
+    feature = "viewer",
+    feature = "conversion",
+    feature = "environment",
+    feature = "math",
+    feature = "network",
+    feature = "database",
+    feature = "random",
+    feature = "generator",
+    feature = "hash",
+    feature = "experimental",
+))]
+mod all_commands {
+    use nu_protocol::engine::EngineState;
+
+    use super::get_context;
+
+    /// Commands of the nushell default context that are left out on purpose
+    const EXCLUDED_COMMANDS: &[&str] = &[
+        // editing the config isn't possible in this environment
+        "config",
+        "config env",
+        "config flatten",
+        "config nu",
+        "config reset",
+        "config use-colors",
+    ];
+
+    #[test]
+    fn it_binds_all_nu_commands() {
+        let mut ctx = get_context();
+        let nu_state = nu_command::add_shell_command_context(nu_cmd_lang::create_default_context());
+        let missing = nu_command_names(&nu_state)
+            .into_iter()
+            .filter(|name| !EXCLUDED_COMMANDS.contains(&name.as_str()))
+            .filter(|name| !ctx.has_fn(name))
+            .collect::<Vec<_>>();
+
+        assert!(missing.is_empty(), "missing commands: {missing:?}");
+    }
+
+    fn nu_command_names(engine_state: &EngineState) -> Vec<String> {
+        engine_state
+            .get_decls_sorted(false)
+            .into_iter()
+            .map(|(name, _)| String::from_utf8_lossy(&name).into_owned())
+            .collect()
+    }
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .build()
+        .unwrap()
+}
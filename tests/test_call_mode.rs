@@ -0,0 +1,73 @@
+use embed_nu::{CallMode, CommandGroupConfig, Context, PipelineData};
+use nu_protocol::Config;
+
+#[test]
+fn it_discards_env_changes_of_isolated_calls() {
+    let mut ctx = get_context();
+    ctx.call_fn_with("set-theme-env", ["dark"], CallMode::Isolated)
+        .unwrap();
+    assert!(get_env(&mut ctx, "THEME").is_none());
+}
+
+#[test]
+fn it_keeps_env_changes_of_env_preserving_calls() {
+    let mut ctx = get_context();
+    ctx.call_fn_with("set-theme", ["dark"], CallMode::PreserveEnv)
+        .unwrap();
+    assert_eq!(get_env(&mut ctx, "THEME").unwrap(), "dark");
+}
+
+#[test]
+fn it_keeps_nu_semantics_for_plain_calls() {
+    let mut ctx = get_context();
+    ctx.call_fn("set-theme", ["dark"]).unwrap();
+    assert!(get_env(&mut ctx, "THEME").is_none());
+    ctx.call_fn("set-theme-env", ["light"]).unwrap();
+    assert_eq!(get_env(&mut ctx, "THEME").unwrap(), "light");
+}
+
+#[test]
+fn it_returns_the_env_diff_of_calls() {
+    let mut ctx = get_context();
+    ctx.eval_raw(
+        r#"$env.MODE = "debug"; $env.LEVEL = "1""#,
+        PipelineData::empty(),
+    )
+    .unwrap();
+    let (_, diff) = ctx
+        .call_fn_env_diff("reconfigure", [] as [String; 0], CallMode::Isolated)
+        .unwrap();
+
+    assert_eq!(diff.added["THEME"].as_str().unwrap(), "dark");
+    assert_eq!(diff.changed["LEVEL"].as_str().unwrap(), "2");
+    assert_eq!(diff.removed, vec![String::from("MODE")]);
+    assert_eq!(get_env(&mut ctx, "MODE").unwrap(), "debug");
+}
+
+fn get_env(ctx: &mut Context, name: &str) -> Option<String> {
+    ctx.eval_raw(format!("$env.{name}?"), PipelineData::empty())
+        .unwrap()
+        .collect_string("", &Config::default())
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_script(String::from(
+            r#"
+            def set-theme [theme: string] { $env.THEME = $theme }
+            def --env set-theme-env [theme: string] { $env.THEME = $theme }
+            def reconfigure [] {
+                $env.THEME = "dark"
+                $env.LEVEL = "2"
+                hide-env MODE
+            }
+            "#,
+        ))
+        .unwrap()
+        .build()
+        .unwrap()
+}
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use embed_nu::{CommandGroupConfig, Context, IntoValue, NewEmpty, PipelineData, RawValue};
+use nu_protocol::{Config, ShellError, Span, Value};
+
+#[test]
+fn it_calls_host_callbacks_with_do() {
+    let mut ctx = get_context();
+    assert_eq!(eval_string(&mut ctx, r#"do $double 21"#), "42");
+}
+
+#[test]
+fn it_passes_host_callbacks_to_each() {
+    let mut ctx = get_context();
+    assert_eq!(
+        eval_string(&mut ctx, r#"[1 2 3] | each $double | str join ",""#),
+        "2,4,6"
+    );
+}
+
+#[test]
+fn it_passes_host_callbacks_as_function_arguments() {
+    let mut ctx = get_context();
+    ctx.eval_raw(
+        r#"def apply [f: closure] { do $f 5 }"#,
+        PipelineData::empty(),
+    )
+    .unwrap();
+    let callback = ctx
+        .create_callback(|args, _input| {
+            let sum = args.iter().map(|v| v.as_int()).sum::<Result<i64, _>>()?;
+            Ok(PipelineData::Value((sum + 1).into_value(), None))
+        })
+        .unwrap();
+    let output = ctx
+        .call_fn("apply", [RawValue(callback)])
+        .unwrap()
+        .into_value(Span::empty())
+        .unwrap();
+    assert_eq!(output.as_int().unwrap(), 6);
+}
+
+#[test]
+fn it_registers_a_single_callback_command() {
+    let mut ctx = get_context();
+    for i in 0..3 {
+        let callback = ctx
+            .create_callback(move |_args, _input| Ok(PipelineData::Value(i.into_value(), None)))
+            .unwrap();
+        ctx.add_var(format!("callback{i}"), RawValue(callback))
+            .unwrap();
+    }
+    assert_eq!(eval_string(&mut ctx, r#"do $callback2"#), "2");
+    assert_eq!(eval_string(&mut ctx, r#"do $double 4"#), "8");
+    assert_eq!(
+        eval_string(
+            &mut ctx,
+            r#"scope commands | where name =~ "embed-nu callback" | length"#
+        ),
+        "1"
+    );
+}
+
+#[test]
+fn it_only_calls_callbacks_through_their_closures() {
+    let mut ctx = get_context();
+    assert!(
+        ctx.eval_raw(r#"embed-nu callback 0"#, PipelineData::empty())
+            .is_err()
+    );
+    assert!(ctx.eval_raw(r#"$callback"#, PipelineData::empty()).is_err());
+}
+
+#[test]
+fn it_passes_explicit_null_arguments() {
+    let mut ctx = get_context();
+    let callback = ctx
+        .create_callback(|args, _input| {
+            Ok(PipelineData::Value((args.len() as i64).into_value(), None))
+        })
+        .unwrap();
+    ctx.add_var("count", RawValue(callback)).unwrap();
+    assert_eq!(eval_string(&mut ctx, r#"do $count null 2"#), "2");
+    assert_eq!(eval_string(&mut ctx, r#"do $count 1 2 3"#), "3");
+    assert_eq!(eval_string(&mut ctx, r#"do $count"#), "0");
+}
+
+#[test]
+fn it_frees_callbacks_with_their_closures() {
+    let mut ctx = get_context();
+    let marker = Arc::new(());
+    let captured = Arc::clone(&marker);
+    let callback = ctx
+        .create_callback(move |_args, _input| {
+            let _ = &captured;
+            Ok(PipelineData::empty())
+        })
+        .unwrap();
+    ctx.add_var("temporary", RawValue(callback)).unwrap();
+    assert_eq!(Arc::strong_count(&marker), 2);
+
+    let replacement = ctx
+        .create_callback(|_args, _input| Ok(PipelineData::empty()))
+        .unwrap();
+    ctx.add_var("temporary", RawValue(replacement)).unwrap();
+    assert_eq!(Arc::strong_count(&marker), 1);
+}
+
+fn double(args: Vec<Value>, input: PipelineData) -> Result<PipelineData, ShellError> {
+    let value = match args.into_iter().next() {
+        Some(value) => value,
+        None => input.into_value(Span::empty())?,
+    };
+
+    Ok(PipelineData::Value(
+        (value.as_int()? * 2).into_value(),
+        None,
+    ))
+}
+
+fn eval_string(ctx: &mut Context, contents: &str) -> String {
+    ctx.eval_raw(contents, PipelineData::empty())
+        .unwrap()
+        .collect_string("", &Config::default())
+        .unwrap()
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_callback_var("double", double)
+        .unwrap()
+        .build()
+        .unwrap()
+}
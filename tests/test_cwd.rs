@@ -0,0 +1,122 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use embed_nu::{CommandGroupConfig, Context, PipelineData};
+use nu_protocol::Config;
+
+#[test]
+fn it_resolves_paths_against_the_context_cwd() {
+    let first_dir = TempDir::new("first");
+    let second_dir = TempDir::new("second");
+    let mut first = get_context(&first_dir.0);
+    let mut second = get_context(&second_dir.0);
+
+    assert_eq!(eval_string(&mut first, r#"open name.txt"#), "first");
+    assert_eq!(eval_string(&mut second, r#"open name.txt"#), "second");
+    assert_eq!(first.cwd().unwrap(), first_dir.0);
+    assert_eq!(
+        eval_string(&mut second, r#"$env.PWD"#),
+        second_dir.0.to_string_lossy()
+    );
+}
+
+#[test]
+fn it_changes_the_cwd_of_a_context() {
+    let first_dir = TempDir::new("set-first");
+    let second_dir = TempDir::new("set-second");
+    let mut ctx = get_context(&first_dir.0);
+    ctx.set_cwd(&second_dir.0).unwrap();
+
+    assert_eq!(eval_string(&mut ctx, r#"open name.txt"#), "set-second");
+    assert_eq!(ctx.cwd().unwrap(), second_dir.0);
+}
+
+#[test]
+fn it_prefers_the_cwd_over_the_inherited_pwd() {
+    let dir = TempDir::new("inherited");
+    let mut ctx = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .cwd(&dir.0)
+        .unwrap()
+        .add_parent_env_vars()
+        .add_script(String::from(r#"let name = open name.txt"#))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(ctx.cwd().unwrap(), dir.0);
+    assert_eq!(eval_string(&mut ctx, r#"open name.txt"#), "inherited");
+}
+
+#[test]
+fn it_sources_scripts_relative_to_the_cwd() {
+    let dir = TempDir::new("sourced");
+    fs::write(dir.0.join("lib.nu"), "def lib-name [] { open name.txt }").unwrap();
+    let mut ctx = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .cwd(&dir.0)
+        .unwrap()
+        .add_parent_env_vars()
+        .add_script(String::from("source lib.nu"))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(eval_string(&mut ctx, "lib-name"), "sourced");
+}
+
+#[test]
+fn it_rejects_invalid_working_directories() {
+    let dir = TempDir::new("invalid");
+    assert!(Context::builder().cwd("relative/path").is_err());
+    assert!(
+        Context::builder()
+            .cwd(dir.0.join("does-not-exist"))
+            .is_err()
+    );
+    assert!(Context::builder().cwd(dir.0.join("name.txt")).is_err());
+
+    let mut ctx = get_context(&dir.0);
+    assert!(ctx.set_cwd("relative/path").is_err());
+    assert_eq!(ctx.cwd().unwrap(), dir.0);
+}
+
+/// A directory containing a `name.txt` with the given name that is removed on drop
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("embed-nu-cwd-{}-{name}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("name.txt"), name).unwrap();
+
+        Self(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn eval_string(ctx: &mut Context, contents: &str) -> String {
+    ctx.eval_raw(contents, PipelineData::empty())
+        .unwrap()
+        .collect_string("", &Config::default())
+        .unwrap()
+}
+
+fn get_context(cwd: &Path) -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .cwd(cwd)
+        .unwrap()
+        .build()
+        .unwrap()
+}
@@ -0,0 +1,134 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use embed_nu::{
+    CommandContext, CommandGroupConfig, Context, IntoValue, NuCommand, PipelineData, host_data,
+};
+use nu_protocol::engine::{Call, Command, EngineState, Stack};
+use nu_protocol::{Config, ShellError, Signature};
+
+struct AppState {
+    greeting: String,
+    calls: AtomicUsize,
+}
+
+/// Greets the given name with the greeting of the host
+#[derive(NuCommand)]
+#[nu(name = "greet")]
+struct Greet {
+    name: String,
+}
+
+impl NuCommand for Greet {
+    fn run(self, ctx: CommandContext, _input: PipelineData) -> Result<PipelineData, ShellError> {
+        let state = ctx.data::<AppState>().expect("missing app state");
+        state.calls.fetch_add(1, Ordering::SeqCst);
+        let output = format!("{} {}", state.greeting, self.name);
+
+        Ok(PipelineData::Value(output.into_value(), None))
+    }
+}
+
+#[derive(Clone)]
+struct GreetingCount;
+
+impl Command for GreetingCount {
+    fn name(&self) -> &str {
+        "greeting count"
+    }
+
+    fn description(&self) -> &str {
+        "Returns the number of greetings"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new("greeting count")
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        _stack: &mut Stack,
+        _call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let state = host_data::<AppState>(engine_state).expect("missing app state");
+        let count = state.calls.load(Ordering::SeqCst);
+
+        Ok(PipelineData::Value(count.into_value(), None))
+    }
+}
+
+#[test]
+fn it_passes_host_data_to_commands() {
+    let mut ctx = get_context();
+    let pipeline = ctx
+        .eval_raw(r#"greet "World""#, PipelineData::empty())
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(output, "Hello World");
+
+    let pipeline = ctx
+        .eval_raw(r#"greeting count"#, PipelineData::empty())
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(output, "1");
+}
+
+#[test]
+fn it_shares_host_data_between_cloned_contexts() {
+    let ctx = get_context();
+    let handles = (0..4)
+        .map(|_| {
+            let mut ctx = ctx.clone();
+            thread::spawn(move || {
+                ctx.eval_raw(r#"greet "Thread""#, PipelineData::empty())
+                    .unwrap();
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let state = ctx.data::<AppState>().unwrap();
+    assert_eq!(state.calls.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn it_hides_host_data_from_the_variable_scope() {
+    let mut ctx = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .with_data(AppState {
+            greeting: String::from("Hello"),
+            calls: AtomicUsize::new(0),
+        })
+        .build()
+        .unwrap();
+    let pipeline = ctx
+        .eval_raw(
+            r#"scope variables | where name =~ "embed-nu" | length"#,
+            PipelineData::empty(),
+        )
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+
+    assert_eq!(output, "0");
+    assert!(!ctx.vars().keys().any(|name| name.contains("embed-nu")));
+    assert!(ctx.data::<AppState>().is_some());
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_data(AppState {
+            greeting: String::from("Hello"),
+            calls: AtomicUsize::new(0),
+        })
+        .add_nu_command::<Greet>()
+        .unwrap()
+        .add_command(GreetingCount)
+        .unwrap()
+        .build()
+        .unwrap()
+}
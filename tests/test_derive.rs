@@ -0,0 +1,129 @@
+use embed_nu::{CommandContext, CommandGroupConfig, Context, IntoValue, NuCommand, PipelineData};
+use nu_protocol::{Config, ShellError};
+
+/// Deploys the given services
+#[derive(NuCommand)]
+#[nu(name = "deploy", category = "custom")]
+struct Deploy {
+    /// The target environment
+    target: String,
+    /// Deploy even if the checks fail
+    #[nu(flag, short = 'f')]
+    force: bool,
+    /// The number of replicas
+    #[nu(flag)]
+    replicas: Option<i64>,
+    /// The services to deploy
+    #[nu(rest)]
+    services: Vec<String>,
+}
+
+impl NuCommand for Deploy {
+    fn run(self, _ctx: CommandContext, _input: PipelineData) -> Result<PipelineData, ShellError> {
+        let output = format!(
+            "{} force={} replicas={} services={}",
+            self.target,
+            self.force,
+            self.replicas.unwrap_or(1),
+            self.services.join(",")
+        );
+
+        Ok(PipelineData::Value(output.into_value(), None))
+    }
+}
+
+/// Greets someone
+#[derive(NuCommand)]
+#[nu(name = "greet")]
+struct Greet {
+    /// The greeting
+    greeting: Option<String>,
+    /// Who to greet
+    name: String,
+    /// How often to greet
+    #[nu(flag)]
+    times: i64,
+}
+
+impl NuCommand for Greet {
+    fn run(self, _ctx: CommandContext, _input: PipelineData) -> Result<PipelineData, ShellError> {
+        let greeting = self.greeting.unwrap_or_else(|| String::from("Hello"));
+        let output = vec![format!("{greeting} {}", self.name); self.times as usize].join(" ");
+
+        Ok(PipelineData::Value(output.into_value(), None))
+    }
+}
+
+#[test]
+fn it_executes_derived_commands() {
+    let mut ctx = get_context();
+    let pipeline = ctx
+        .eval_raw(
+            r#"deploy prod -f --replicas 3 api web"#,
+            PipelineData::empty(),
+        )
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(output, "prod force=true replicas=3 services=api,web");
+
+    let pipeline = ctx
+        .eval_raw(r#"deploy staging"#, PipelineData::empty())
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(output, "staging force=false replicas=1 services=");
+}
+
+#[test]
+fn it_reports_missing_arguments_of_derived_commands() {
+    let mut ctx = get_context();
+    let result = ctx.eval_raw(r#"deploy --force"#, PipelineData::empty());
+    assert!(result.is_err());
+}
+
+#[test]
+fn it_requires_flags_that_are_not_optional() {
+    let mut ctx = get_context();
+    let result = ctx.eval_raw(r#"greet Hi World"#, PipelineData::empty());
+    assert!(result.is_err());
+
+    let pipeline = ctx
+        .eval_raw(r#"greet Hi World --times 2"#, PipelineData::empty())
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(output, "Hi World Hi World");
+}
+
+#[test]
+fn it_keeps_optional_fields_before_required_ones_in_place() {
+    let mut ctx = get_context();
+    let pipeline = ctx
+        .eval_raw(r#"greet null World --times 1"#, PipelineData::empty())
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(output, "Hello World");
+}
+
+#[test]
+fn it_uses_doc_comments_as_descriptions() {
+    let mut ctx = get_context();
+    let pipeline = ctx
+        .eval_raw(
+            r#"scope commands | where name == deploy | get 0.description"#,
+            PipelineData::empty(),
+        )
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(output, "Deploys the given services");
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_nu_command::<Deploy>()
+        .unwrap()
+        .add_nu_command::<Greet>()
+        .unwrap()
+        .build()
+        .unwrap()
+}
@@ -0,0 +1,189 @@
+use std::process::Command;
+
+use embed_nu::{CommandGroupConfig, Context, EnvFilter, PipelineData};
+use nu_protocol::Config;
+
+#[test]
+fn it_reads_env_vars_set_by_scripts() {
+    let mut ctx = get_context();
+    ctx.eval_raw(r#"$env.FOO = "bar""#, PipelineData::empty())
+        .unwrap();
+    assert_eq!(ctx.get_env("FOO").unwrap().as_str().unwrap(), "bar");
+    assert!(ctx.env_vars().contains_key("FOO"));
+}
+
+#[test]
+fn it_sets_env_vars_for_following_evals() {
+    let mut ctx = get_context();
+    ctx.set_env("GREETING", "hello").unwrap();
+    assert_eq!(eval_string(&mut ctx, r#"$env.GREETING"#), "hello");
+}
+
+#[test]
+fn it_removes_env_vars() {
+    let mut ctx = get_context();
+    ctx.set_env("TEMP_VAR", "value").unwrap();
+    let removed = ctx.remove_env("TEMP_VAR").unwrap();
+    assert_eq!(removed.as_str().unwrap(), "value");
+    assert!(ctx.get_env("TEMP_VAR").is_none());
+    assert!(ctx.remove_env("TEMP_VAR").is_none());
+    assert_eq!(
+        eval_string(&mut ctx, r#"$env.TEMP_VAR? | default "gone""#),
+        "gone"
+    );
+}
+
+#[test]
+fn it_converts_path_into_a_list() {
+    let mut ctx = get_context();
+    let path = std::env::join_paths(["/usr/bin", "/opt/bin"]).unwrap();
+    ctx.set_env("PATH", path.to_string_lossy().to_string())
+        .unwrap();
+    assert_eq!(eval_string(&mut ctx, r#"$env.PATH | length"#), "2");
+    assert_eq!(
+        ctx.get_env_string("PATH").unwrap().unwrap(),
+        path.to_string_lossy()
+    );
+}
+
+#[test]
+fn it_applies_env_conversions() {
+    let mut ctx = get_context();
+    ctx.eval_raw(
+        r#"$env.ENV_CONVERSIONS = {
+            TAGS: {
+                from_string: {|s| $s | split row "," }
+                to_string: {|v| $v | str join "," }
+            }
+        }"#,
+        PipelineData::empty(),
+    )
+    .unwrap();
+    ctx.set_env("TAGS", "a,b,c").unwrap();
+    assert_eq!(eval_string(&mut ctx, r#"$env.TAGS | length"#), "3");
+    assert_eq!(ctx.get_env_string("TAGS").unwrap().unwrap(), "a,b,c");
+}
+
+#[test]
+fn it_filters_env_vars() {
+    let filter = EnvFilter::new()
+        .allow_prefix("EMBED_NU_TEST_")
+        .allow(["PATH"])
+        .deny(["EMBED_NU_TEST_SECRET"])
+        .filter(|name, _| !name.ends_with("_TOKEN"));
+
+    assert!(filter.matches("EMBED_NU_TEST_PUBLIC", "visible"));
+    assert!(filter.matches("PATH", "/usr/bin"));
+    assert!(!filter.matches("EMBED_NU_TEST_SECRET", "hidden"));
+    assert!(!filter.matches("EMBED_NU_TEST_TOKEN", "hidden"));
+    assert!(!filter.matches("HOME", "/root"));
+
+    let filter = EnvFilter::new()
+        .deny_prefix("AWS_")
+        .filter(|_, value| !value.is_empty());
+
+    assert!(filter.matches("HOME", "/root"));
+    assert!(!filter.matches("AWS_SECRET_ACCESS_KEY", "hidden"));
+    assert!(!filter.matches("EMPTY", ""));
+}
+
+#[test]
+fn it_filters_inherited_env_vars() {
+    if !in_child_process(
+        "it_filters_inherited_env_vars",
+        &[
+            ("EMBED_NU_TEST_PUBLIC", "visible"),
+            ("EMBED_NU_TEST_SECRET", "hidden"),
+            ("EMBED_NU_TEST_TOKEN", "hidden"),
+        ],
+    ) {
+        return;
+    }
+    let ctx = Context::builder()
+        .add_parent_env_vars_filtered(
+            &EnvFilter::new()
+                .allow_prefix("EMBED_NU_TEST_")
+                .allow(["PATH"])
+                .deny(["EMBED_NU_TEST_SECRET"])
+                .filter(|name, _| !name.ends_with("_TOKEN")),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        ctx.get_env("EMBED_NU_TEST_PUBLIC")
+            .unwrap()
+            .as_str()
+            .unwrap(),
+        "visible"
+    );
+    assert!(ctx.get_env("EMBED_NU_TEST_SECRET").is_none());
+    assert!(ctx.get_env("EMBED_NU_TEST_TOKEN").is_none());
+    assert!(ctx.get_env("HOME").is_none());
+    assert!(ctx.get_env("PATH").unwrap().as_list().is_ok());
+}
+
+#[test]
+fn it_applies_env_conversions_to_inherited_env_vars() {
+    if !in_child_process(
+        "it_applies_env_conversions_to_inherited_env_vars",
+        &[("EMBED_NU_TEST_LIST", "a:b")],
+    ) {
+        return;
+    }
+    let mut ctx = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_parent_env_vars_filtered(&EnvFilter::new().allow(["EMBED_NU_TEST_LIST"]))
+        .add_script(String::from(
+            r#"$env.ENV_CONVERSIONS = {
+                EMBED_NU_TEST_LIST: {
+                    from_string: {|s| $s | split row ":" }
+                    to_string: {|v| $v | str join ":" }
+                }
+            }"#,
+        ))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        eval_string(&mut ctx, r#"$env.EMBED_NU_TEST_LIST | length"#),
+        "2"
+    );
+}
+
+/// Changing the environment of the test process isn't thread safe, so tests
+/// depending on inherited variables run again in a child process with the variables set.
+/// Returns true inside of the child process
+fn in_child_process(test: &str, vars: &[(&str, &str)]) -> bool {
+    if std::env::var_os(CHILD_PROCESS_VAR).is_some() {
+        return true;
+    }
+    let status = Command::new(std::env::current_exe().unwrap())
+        .args([test, "--exact"])
+        .env(CHILD_PROCESS_VAR, "1")
+        .envs(vars.iter().copied())
+        .status()
+        .unwrap();
+    assert!(status.success(), "{test} failed in the child process");
+
+    false
+}
+
+const CHILD_PROCESS_VAR: &str = "EMBED_NU_TEST_CHILD_PROCESS";
+
+fn eval_string(ctx: &mut Context, contents: &str) -> String {
+    ctx.eval_raw(contents, PipelineData::empty())
+        .unwrap()
+        .collect_string("", &Config::default())
+        .unwrap()
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .build()
+        .unwrap()
+}
@@ -1,6 +1,8 @@
-use embed_nu::{CommandGroupConfig, Context, PipelineData};
-use embed_nu::{IntoValue, NewEmpty, rusty_value::*};
-use nu_protocol::engine::{Call, Command, EngineState, Stack};
+use embed_nu::{CallExt, CommandGroupConfig, Context, PipelineData, ShadowPolicy};
+use embed_nu::{IntoValue, NewEmpty, RawValue, rusty_value::*};
+use nu_cmd_lang::Def;
+use nu_parser::parse;
+use nu_protocol::engine::{Call, Command, EngineState, Stack, StateWorkingSet};
 use nu_protocol::{Config, Signature, Span, SyntaxShape};
 
 #[test]
@@ -79,7 +81,7 @@ fn it_executes_functions() {
     )
     .unwrap();
     ctx.call_fn("hello", [] as [String; 0]).unwrap();
-    assert!(ctx.has_fn("world") == false);
+    assert!(!ctx.has_fn("world"));
 
     let test_arg = TestArg {
         foo: String::from("Hello World"),
@@ -89,6 +91,24 @@ fn it_executes_functions() {
     ctx.print_pipeline(pipeline).unwrap();
 }
 
+#[test]
+fn it_passes_closures_with_captured_values_to_functions() {
+    let mut ctx = get_context();
+    ctx.eval_raw("def apply [f: closure] { do $f }", PipelineData::empty())
+        .unwrap();
+    let closure = ctx
+        .eval_raw("do { let x = 5; {|| $x + 1 } }", PipelineData::empty())
+        .unwrap()
+        .into_value(Span::empty())
+        .unwrap();
+    let output = ctx
+        .call_fn("apply", [RawValue(closure)])
+        .unwrap()
+        .into_value(Span::empty())
+        .unwrap();
+    assert_eq!(output.as_int().unwrap(), 6);
+}
+
 #[test]
 fn it_executes_custom_commands() {
     let mut ctx = get_context();
@@ -99,6 +119,210 @@ fn it_executes_custom_commands() {
     assert_eq!(string_output, String::from("HELLO WORLD"))
 }
 
+#[cfg(feature = "network")]
+#[test]
+fn it_overrides_builtin_commands() {
+    let mut ctx = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .override_command(HostHttpGet)
+        .unwrap()
+        .build()
+        .unwrap();
+    let pipeline = ctx
+        .eval_raw(
+            r#"http get --raw "https://example.com""#,
+            PipelineData::empty(),
+        )
+        .unwrap();
+    let string_output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(
+        string_output,
+        String::from("served by host: https://example.com")
+    )
+}
+
+#[test]
+fn it_refuses_to_override_unknown_commands() {
+    let result = Context::builder().override_command(HostHttpGet);
+    assert!(result.is_err());
+}
+
+#[test]
+fn it_forbids_shadowing_protected_commands() {
+    let mut ctx = get_protected_context(ShadowPolicy::Forbid);
+    let result = ctx.eval_raw(r#"def custom_upper [] { "lower" }"#, PipelineData::empty());
+    assert!(result.is_err());
+
+    let result = ctx.eval_raw(
+        r#"module shadow { export def custom_upper [] { "lower" } }"#,
+        PipelineData::empty(),
+    );
+    assert!(result.is_err());
+
+    let result = ctx.eval_raw(r#"hide custom_upper"#, PipelineData::empty());
+    assert!(result.is_err());
+}
+
+#[test]
+fn it_allows_shadowing_protected_commands_in_modules() {
+    let mut ctx = get_protected_context(ShadowPolicy::AllowInModules);
+    ctx.eval_raw(
+        r#"module shadow { export def custom_upper [] { "lower" } }"#,
+        PipelineData::empty(),
+    )
+    .unwrap();
+
+    let result = ctx.eval_raw(r#"alias custom_upper = echo"#, PipelineData::empty());
+    assert!(result.is_err());
+
+    let result = ctx.eval_raw(r#"use shadow custom_upper"#, PipelineData::empty());
+    assert!(result.is_err());
+
+    let result = ctx.eval_raw(
+        r#"do { def custom_upper [] { "lower" } }"#,
+        PipelineData::empty(),
+    );
+    assert!(result.is_err());
+
+    let pipeline = ctx
+        .eval_raw(r#"custom_upper "still protected""#, PipelineData::empty())
+        .unwrap();
+    let string_output = pipeline.collect_string(" ", &Config::default()).unwrap();
+    assert_eq!(string_output, String::from("STILL PROTECTED"))
+}
+
+#[test]
+fn it_reports_protected_aliases_at_the_alias() {
+    let mut ctx = get_protected_context(ShadowPolicy::Forbid);
+    let script = r#"alias custom_upper = echo; let x = 1"#;
+    let err = format!(
+        "{:?}",
+        ctx.eval_raw(script, PipelineData::empty()).unwrap_err()
+    );
+
+    assert!(err.contains("is protected"), "{err}");
+    assert!(!err.contains("start: 0,"), "{err}");
+}
+
+#[test]
+fn it_checks_added_blocks_for_protected_commands() {
+    // added blocks reference declarations by id, so the block is parsed
+    // in a state with the same declarations as the builder
+    let mut engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    working_set.add_decl(Box::new(Def));
+    working_set.add_decl(Box::new(CustomCommand));
+    let delta = working_set.render();
+    engine_state.merge_delta(delta).unwrap();
+
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    let block = parse(
+        &mut working_set,
+        None,
+        br#"def custom_upper [] { "lower" }"#,
+        false,
+    );
+    let result = Context::builder()
+        .add_command(Def)
+        .unwrap()
+        .add_command(CustomCommand)
+        .unwrap()
+        .protect_command("custom_upper", ShadowPolicy::AllowInModules)
+        .unwrap()
+        .add_block(block.as_ref().clone())
+        .build();
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn it_executes_closure_commands() {
+    let mut ctx = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_fn("slugify", |text: String, max: Option<i64>| {
+            let slug = text.to_lowercase().replace(' ', "-");
+            let max = max.map(|m| m as usize).unwrap_or(slug.len());
+
+            Ok::<_, String>(slug.chars().take(max).collect::<String>())
+        })
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let pipeline = ctx
+        .eval_raw(r#"slugify "Hello World""#, PipelineData::empty())
+        .unwrap();
+    let string_output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(string_output, String::from("hello-world"));
+
+    let pipeline = ctx
+        .eval_raw(r#"slugify "Hello World" 5"#, PipelineData::empty())
+        .unwrap();
+    let string_output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(string_output, String::from("hello"));
+
+    let result = ctx.eval_raw(r#"slugify "Hello World" "five""#, PipelineData::empty());
+    assert!(result.is_err());
+}
+
+#[test]
+fn it_keeps_options_before_required_parameters_in_place() {
+    let mut ctx = Context::builder()
+        .add_fn("greet", |greeting: Option<String>, name: String| {
+            let greeting = greeting.unwrap_or_else(|| String::from("Hello"));
+
+            Ok::<_, String>(format!("{greeting} {name}"))
+        })
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let pipeline = ctx
+        .eval_raw(r#"greet null "World""#, PipelineData::empty())
+        .unwrap();
+    let string_output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(string_output, String::from("Hello World"));
+
+    let pipeline = ctx
+        .eval_raw(r#"greet "Hi" "World""#, PipelineData::empty())
+        .unwrap();
+    let string_output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(string_output, String::from("Hi World"));
+}
+
+#[test]
+fn it_reports_closure_command_errors_at_the_call() {
+    let mut ctx = Context::builder()
+        .add_fn("fail", || Err::<i64, _>("failed on purpose"))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let err = format!(
+        "{:?}",
+        ctx.eval_raw(r#"fail"#, PipelineData::empty()).unwrap_err()
+    );
+    assert!(err.contains("failed on purpose"), "{err}");
+    assert!(
+        err.contains("span: Some(Span { start: 0, end: 4 })"),
+        "{err}"
+    );
+}
+
+fn get_protected_context(policy: ShadowPolicy) -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_command(CustomCommand)
+        .unwrap()
+        .protect_command("custom_upper", policy)
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
 fn get_context() -> Context {
     Context::builder()
         .with_command_groups(CommandGroupConfig::default().all_groups(true))
@@ -132,14 +356,19 @@ impl Command for CustomCommand {
             .category(nu_protocol::Category::Experimental)
     }
 
+    // the argument is read from its expression, which IR only keeps on request
+    fn requires_ast_for_arguments(&self) -> bool {
+        true
+    }
+
     fn run(
         &self,
-        engine_state: &EngineState,
+        _engine_state: &EngineState,
         stack: &mut Stack,
         call: &Call,
         _input: PipelineData,
     ) -> Result<PipelineData, nu_protocol::ShellError> {
-        let string_input = call.positional_nth(&stack, 0).unwrap();
+        let string_input = call.positional_nth(stack, 0).unwrap();
         let string_input = string_input.as_string().unwrap();
         let upper = string_input.to_uppercase();
         println!("{upper}");
@@ -147,3 +376,35 @@ impl Command for CustomCommand {
         Ok(PipelineData::Value(upper.into_value(), None))
     }
 }
+
+#[derive(Clone)]
+struct HostHttpGet;
+
+impl Command for HostHttpGet {
+    fn name(&self) -> &str {
+        "http get"
+    }
+
+    fn description(&self) -> &str {
+        "Fetches the url through the host"
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::new("http get")
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, nu_protocol::ShellError> {
+        let url: String = call.req(engine_state, stack, 0)?;
+
+        Ok(PipelineData::Value(
+            format!("served by host: {url}").into_value(),
+            None,
+        ))
+    }
+}
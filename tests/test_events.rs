@@ -0,0 +1,85 @@
+use std::sync::{Arc, Mutex};
+
+use embed_nu::{CommandGroupConfig, Context, PipelineData};
+use nu_protocol::Config;
+
+#[test]
+fn it_delivers_script_events_to_subscribers() {
+    let mut ctx = get_context();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&received);
+    ctx.subscribe("progress", move |event| {
+        sink.lock().unwrap().push(event.payload.as_int().unwrap());
+    });
+    ctx.eval_raw(
+        r#"1..3 | each {|i| emit progress $i } | ignore"#,
+        PipelineData::empty(),
+    )
+    .unwrap();
+    assert_eq!(*received.lock().unwrap(), vec![1, 2, 3]);
+}
+
+#[test]
+fn it_delivers_script_events_to_channels() {
+    let mut ctx = get_context();
+    let rx = ctx.subscribe_channel("done");
+    ctx.eval_raw(r#"{status: "ok"} | emit done"#, PipelineData::empty())
+        .unwrap();
+    let event = rx.try_recv().unwrap();
+    assert_eq!(event.name, "done");
+    assert_eq!(
+        event
+            .payload
+            .get_data_by_key("status")
+            .unwrap()
+            .as_str()
+            .unwrap(),
+        "ok"
+    );
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn it_triggers_script_handlers() {
+    let mut ctx = get_context();
+    ctx.eval_raw(
+        r#"on greet {|name| $"Hello ($name)" }"#,
+        PipelineData::empty(),
+    )
+    .unwrap();
+    let outputs = ctx.trigger("greet", "World").unwrap();
+    assert_eq!(outputs.len(), 1);
+    let output = outputs
+        .into_iter()
+        .next()
+        .unwrap()
+        .collect_string("", &Config::default())
+        .unwrap();
+    assert_eq!(output, "Hello World");
+    assert!(ctx.trigger("unknown", "World").unwrap().is_empty());
+}
+
+#[test]
+fn it_keeps_handlers_of_clones_in_the_clone() {
+    let mut ctx = get_context();
+    let mut clone = ctx.clone();
+    clone
+        .eval_raw(r#"on ping {|x| $x }"#, PipelineData::empty())
+        .unwrap();
+
+    assert!(ctx.trigger("ping", 1).unwrap().is_empty());
+    assert_eq!(clone.trigger("ping", 1).unwrap().len(), 1);
+    ctx.eval_raw(r#"on ping {|x| $x * 2 }"#, PipelineData::empty())
+        .unwrap();
+    assert_eq!(clone.trigger("ping", 1).unwrap().len(), 1);
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .with_events()
+        .unwrap()
+        .build()
+        .unwrap()
+}
@@ -0,0 +1,127 @@
+use std::sync::{Arc, Mutex};
+
+use embed_nu::{
+    CommandGroupConfig, Context, ExternalAction, ExternalCall, IntoValue, PipelineData,
+};
+use nu_protocol::{Config, Value};
+
+#[test]
+fn it_answers_external_calls_with_synthetic_output() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let handler_calls = Arc::clone(&calls);
+    let mut ctx = get_context(move |call: ExternalCall| {
+        handler_calls
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", call.program, join_args(&call.args)));
+        ExternalAction::Respond(PipelineData::Value("clean".into_value(), None))
+    });
+    let pipeline = ctx
+        .eval_raw(r#"^git status --short"#, PipelineData::empty())
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+
+    assert_eq!(output, String::from("clean"));
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![String::from("git status --short")]
+    );
+}
+
+#[test]
+fn it_denies_external_calls() {
+    let mut ctx = get_context(|call: ExternalCall| {
+        ExternalAction::Deny(format!("{} is not allowed", call.program))
+    });
+    let result = ctx.eval_raw(r#"^rm -rf /tmp/does-not-exist"#, PipelineData::empty());
+
+    let err = format!("{:?}", result.unwrap_err());
+
+    assert!(err.contains("rm is not allowed"), "{err}");
+}
+
+#[cfg(all(unix, feature = "system"))]
+#[test]
+fn it_runs_rewritten_external_calls() {
+    let mut ctx = get_context(|mut call: ExternalCall| {
+        let name = call.args.remove(0);
+        call.program = String::from("sh");
+        call.args = vec![
+            "-c".into_value(),
+            r#"printf '%s %s' "$GREETING" "$1""#.into_value(),
+            "sh".into_value(),
+            name,
+        ];
+        call.env
+            .insert(String::from("PATH"), "/bin:/usr/bin".into_value());
+        call.env
+            .insert(String::from("GREETING"), "hello".into_value());
+        ExternalAction::Run(call)
+    });
+    let pipeline = ctx
+        .eval_raw(r#"^greet world | collect"#, PipelineData::empty())
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+
+    assert_eq!(output, String::from("hello world"));
+}
+
+#[cfg(all(unix, feature = "system"))]
+#[test]
+fn it_expands_globs_of_approved_external_calls() {
+    let dir = std::env::temp_dir().join(format!("embed-nu-glob-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let _guard = RemoveOnDrop(dir.clone());
+    std::fs::write(dir.join("Cargo.toml"), "").unwrap();
+
+    let mut ctx = Context::builder()
+        .on_external(|mut call: ExternalCall| {
+            assert!(matches!(call.args[0], Value::Glob { .. }));
+            call.env
+                .insert(String::from("PATH"), "/bin:/usr/bin".into_value());
+            ExternalAction::Run(call)
+        })
+        .unwrap()
+        .add_env_var("PWD", dir.to_string_lossy().to_string())
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .build()
+        .unwrap();
+    let pipeline = ctx
+        .eval_raw(r#"^echo *.toml "*.toml" | collect"#, PipelineData::empty())
+        .unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+
+    assert_eq!(output.trim(), "Cargo.toml *.toml");
+}
+
+/// Removes the directory on drop
+#[cfg(all(unix, feature = "system"))]
+struct RemoveOnDrop(std::path::PathBuf);
+
+#[cfg(all(unix, feature = "system"))]
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn join_args(args: &[Value]) -> String {
+    args.iter()
+        .map(|arg| arg.coerce_str().unwrap().into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn get_context<F: Fn(ExternalCall) -> ExternalAction + Send + Sync + 'static>(
+    handler: F,
+) -> Context {
+    Context::builder()
+        .on_external(handler)
+        .unwrap()
+        .add_env_var("PWD", std::env::temp_dir().to_string_lossy().to_string())
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .build()
+        .unwrap()
+}
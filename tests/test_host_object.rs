@@ -0,0 +1,112 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use embed_nu::{CommandGroupConfig, Context, HostObject, HostRef, IntoValue, PipelineData};
+use nu_protocol::{Config, Value};
+
+struct Document {
+    title: String,
+    pages: i64,
+    drops: Option<Arc<AtomicUsize>>,
+}
+
+impl HostObject for Document {
+    fn type_name(&self) -> String {
+        String::from("document")
+    }
+
+    fn get_field(&self, name: &str) -> Option<Value> {
+        match name {
+            "title" => Some(self.title.clone().into_value()),
+            "pages" => Some(self.pages.into_value()),
+            _ => None,
+        }
+    }
+}
+
+impl Drop for Document {
+    fn drop(&mut self) {
+        if let Some(drops) = &self.drops {
+            drops.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[test]
+fn it_exposes_host_objects_to_scripts() {
+    let mut ctx = get_context();
+    ctx.add_var("doc", HostRef::new(document("Report")))
+        .unwrap();
+
+    assert_eq!(eval_string(&mut ctx, r#"$doc.title"#), "Report");
+    assert_eq!(eval_string(&mut ctx, r#"$doc | describe"#), "document");
+    assert!(
+        ctx.eval_raw(r#"$doc.author"#, PipelineData::empty())
+            .is_err()
+    );
+}
+
+#[test]
+fn it_passes_host_objects_back_to_commands() {
+    let mut ctx = get_context();
+    ctx.add_var("doc", HostRef::new(document("Report")))
+        .unwrap();
+
+    assert_eq!(eval_string(&mut ctx, r#"page-count $doc"#), "12");
+    assert_eq!(
+        eval_string(&mut ctx, r#"open-doc "Notes" | get title"#),
+        "Notes"
+    );
+    assert!(
+        ctx.eval_raw(r#"page-count "not a document""#, PipelineData::empty())
+            .is_err()
+    );
+}
+
+#[test]
+fn it_drops_host_objects_with_the_context() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let mut doc = document("Dropped");
+    doc.drops = Some(Arc::clone(&drops));
+    let doc = HostRef::new(doc);
+    let weak = Arc::downgrade(&doc.0);
+    let mut ctx = get_context();
+    ctx.add_var("doc", doc).unwrap();
+    assert!(weak.upgrade().is_some());
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+    drop(ctx);
+    assert!(weak.upgrade().is_none());
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+}
+
+fn document(title: &str) -> Document {
+    Document {
+        title: title.to_string(),
+        pages: 12,
+        drops: None,
+    }
+}
+
+fn eval_string(ctx: &mut Context, contents: &str) -> String {
+    ctx.eval_raw(contents, PipelineData::empty())
+        .unwrap()
+        .collect_string("", &Config::default())
+        .unwrap()
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_fn("page-count", |doc: HostRef<Document>| {
+            Ok::<_, String>(doc.pages)
+        })
+        .unwrap()
+        .add_fn("open-doc", |title: String| {
+            Ok::<_, String>(HostRef::new(document(&title)))
+        })
+        .unwrap()
+        .build()
+        .unwrap()
+}
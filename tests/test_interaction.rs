@@ -0,0 +1,139 @@
+use embed_nu::{
+    CommandGroupConfig, ConfirmPrompt, Context, InteractionProvider, NonInteractive, PipelineData,
+    PromptResponse, SelectPrompt, TextPrompt,
+};
+use nu_protocol::Config;
+
+struct ScriptedUser;
+
+impl InteractionProvider for ScriptedUser {
+    fn input(&self, prompt: TextPrompt) -> PromptResponse<String> {
+        match prompt.message.as_str() {
+            "Name: " => PromptResponse::Value(String::from("Ferris")),
+            "Password: " if prompt.secret => PromptResponse::Value(String::from("hunter2")),
+            "Port: " => PromptResponse::Value(String::new()),
+            _ => PromptResponse::Cancelled,
+        }
+    }
+
+    fn select(&self, prompt: SelectPrompt) -> PromptResponse<Vec<usize>> {
+        let selected = prompt
+            .options
+            .iter()
+            .enumerate()
+            .filter(|(_, option)| option.starts_with('b'))
+            .map(|(i, _)| i)
+            .collect();
+        PromptResponse::Value(selected)
+    }
+
+    fn confirm(&self, prompt: ConfirmPrompt) -> PromptResponse<bool> {
+        PromptResponse::Value(prompt.message.contains("safe"))
+    }
+}
+
+#[test]
+fn it_routes_input_to_the_provider() {
+    let mut ctx = get_context(ScriptedUser);
+    assert_eq!(eval_string(&mut ctx, r#"input "Name: ""#), "Ferris");
+    assert_eq!(eval_string(&mut ctx, r#"input -s "Password: ""#), "hunter2");
+    assert_eq!(
+        eval_string(&mut ctx, r#"input --default "8080" "Port: ""#),
+        "8080"
+    );
+    assert!(
+        ctx.eval_raw(r#"input "Anything else? ""#, PipelineData::empty())
+            .is_err()
+    );
+}
+
+#[test]
+fn it_routes_selections_to_the_provider() {
+    let mut ctx = get_context(ScriptedUser);
+    assert_eq!(
+        eval_string(&mut ctx, r#"["apple", "banana", "cherry"] | input list"#),
+        "banana"
+    );
+    assert_eq!(
+        eval_string(
+            &mut ctx,
+            r#"["bar", "foo", "baz"] | input list --multi | str join ",""#
+        ),
+        "bar,baz"
+    );
+    assert_eq!(
+        eval_string(
+            &mut ctx,
+            r#"[{name: "a"}, {name: "b"}] | input list --index --display name"#
+        ),
+        "1"
+    );
+}
+
+#[test]
+fn it_routes_confirmations_to_the_provider() {
+    let mut ctx = get_context(ScriptedUser);
+    assert_eq!(eval_string(&mut ctx, r#"confirm "Is it safe?""#), "true");
+    assert_eq!(eval_string(&mut ctx, r#"confirm "Delete it?""#), "false");
+}
+
+#[test]
+fn it_fails_prompts_without_interaction() {
+    let mut ctx = get_context(NonInteractive);
+    assert!(
+        ctx.eval_raw(r#"input "Name: ""#, PipelineData::empty())
+            .is_err()
+    );
+    assert!(
+        ctx.eval_raw(r#"[1 2 3] | input list"#, PipelineData::empty())
+            .is_err()
+    );
+    assert!(
+        ctx.eval_raw(r#"confirm "Continue?""#, PipelineData::empty())
+            .is_err()
+    );
+}
+
+#[test]
+fn it_fails_prompts_by_default() {
+    let mut ctx = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .build()
+        .unwrap();
+    assert!(
+        ctx.eval_raw(r#"input "Name: ""#, PipelineData::empty())
+            .is_err()
+    );
+    assert!(
+        ctx.eval_raw(r#"input listen --types [key]"#, PipelineData::empty())
+            .is_err()
+    );
+}
+
+#[test]
+fn it_keeps_the_signature_of_the_builtin_input() {
+    let mut ctx = get_context(ScriptedUser);
+    assert_eq!(eval_string(&mut ctx, r#"input --numchar 2 "Name: ""#), "Fe");
+    assert!(
+        ctx.eval_raw(r#"input --not-a-flag "Name: ""#, PipelineData::empty())
+            .is_err()
+    );
+}
+
+fn eval_string(ctx: &mut Context, contents: &str) -> String {
+    ctx.eval_raw(contents, PipelineData::empty())
+        .unwrap()
+        .collect_string("", &Config::default())
+        .unwrap()
+}
+
+fn get_context<P: InteractionProvider + 'static>(provider: P) -> Context {
+    Context::builder()
+        .with_interaction(provider)
+        .unwrap()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .build()
+        .unwrap()
+}
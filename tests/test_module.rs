@@ -0,0 +1,99 @@
+use embed_nu::{CommandGroupConfig, Context, ModuleBuilder, PipelineData};
+use nu_protocol::Config;
+
+#[test]
+fn it_imports_host_modules() {
+    let mut ctx = get_context();
+    assert_eq!(
+        eval_string(&mut ctx, r#"use myapp; myapp config get theme"#),
+        "dark"
+    );
+    assert_eq!(
+        eval_string(&mut ctx, r#"use myapp; myapp greet "World""#),
+        "Hello World"
+    );
+    assert_eq!(
+        eval_string(&mut ctx, r#"use myapp; $myapp.version"#),
+        "1.2.0"
+    );
+}
+
+#[test]
+fn it_keeps_module_commands_out_of_the_global_scope() {
+    let mut ctx = get_context();
+    assert!(!ctx.has_fn("config get"));
+    assert!(
+        ctx.eval_raw(r#"config get theme"#, PipelineData::empty())
+            .is_err()
+    );
+}
+
+#[test]
+fn it_uses_host_modules_as_overlays() {
+    let mut ctx = get_context();
+    ctx.eval_raw(r#"overlay use myapp"#, PipelineData::empty())
+        .unwrap();
+    assert_eq!(eval_string(&mut ctx, r#"config get theme"#), "dark");
+}
+
+#[test]
+fn it_hides_host_module_commands() {
+    let mut ctx = get_context();
+    assert_eq!(
+        eval_string(&mut ctx, r#"use myapp *; config get theme"#),
+        "dark"
+    );
+
+    // commands imported by previous scripts stay visible until the hiding script is done
+    let mut ctx = get_context();
+    assert!(
+        ctx.eval_raw(
+            r#"use myapp *; hide "config get"; config get theme"#,
+            PipelineData::empty()
+        )
+        .is_err()
+    );
+
+    let mut ctx = get_context();
+    assert!(
+        ctx.eval_raw(
+            r#"use myapp *; hide greet; greet "World""#,
+            PipelineData::empty()
+        )
+        .is_err()
+    );
+}
+
+fn eval_string(ctx: &mut Context, contents: &str) -> String {
+    ctx.eval_raw(contents, PipelineData::empty())
+        .unwrap()
+        .collect_string("", &Config::default())
+        .unwrap()
+}
+
+fn get_context() -> Context {
+    let module = ModuleBuilder::new("myapp")
+        .add_fn("config get", |key: String| {
+            if key == "theme" {
+                Ok(String::from("dark"))
+            } else {
+                Err(format!("unknown key {key}"))
+            }
+        })
+        .add_const("version", "1.2.0")
+        .add_source(
+            r#"
+            export def greet [name: string] {
+                $"Hello ($name)"
+            }
+            "#,
+        );
+
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_module(module)
+        .unwrap()
+        .build()
+        .unwrap()
+}
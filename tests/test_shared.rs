@@ -0,0 +1,191 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use embed_nu::{CommandGroupConfig, Context, NewEmpty, PipelineData, SharedContext};
+use nu_protocol::{
+    Config, ShellError, Signature, Span,
+    engine::{Call, Command, EngineState, Stack},
+};
+
+#[test]
+fn it_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedContext>();
+}
+
+#[test]
+fn it_calls_functions_from_many_threads() {
+    let ctx = get_context();
+    thread::scope(|scope| {
+        for t in 0..8i64 {
+            let ctx = &ctx;
+            scope.spawn(move || {
+                for i in 0..50i64 {
+                    let n = t * 100 + i;
+                    let value = ctx
+                        .call_fn("square", [n])
+                        .unwrap()
+                        .into_value(Span::empty())
+                        .unwrap();
+                    assert_eq!(value.as_int().unwrap(), n * n);
+                }
+            });
+        }
+    });
+}
+
+#[test]
+fn it_evaluates_scripts_from_many_threads() {
+    let ctx = get_context();
+    thread::scope(|scope| {
+        for t in 0..8i64 {
+            let ctx = &ctx;
+            scope.spawn(move || {
+                for i in 0..50i64 {
+                    let n = t * 100 + i;
+                    let output = ctx
+                        .eval_raw(
+                            format!(r#"def local [x: int] {{ $x * 2 }}; local {n} | into string"#),
+                            PipelineData::empty(),
+                        )
+                        .unwrap()
+                        .collect_string("", &Config::default())
+                        .unwrap();
+                    assert_eq!(output, (n * 2).to_string());
+                }
+            });
+        }
+    });
+    assert!(!ctx.has_fn("local"));
+}
+
+#[test]
+fn it_isolates_the_stacks_of_evaluations() {
+    let ctx = get_context();
+    ctx.eval_raw(r#"$env.COUNTER = 1"#, PipelineData::empty())
+        .unwrap();
+    assert!(
+        ctx.eval_raw(r#"$env.COUNTER"#, PipelineData::empty())
+            .is_err()
+    );
+}
+
+#[test]
+fn it_does_not_copy_the_definitions_per_evaluation() {
+    let clones = Arc::new(AtomicUsize::new(0));
+    let ctx: Context = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_command(CountedCommand(Arc::clone(&clones)))
+        .unwrap()
+        .add_script(String::from(r#"def square [x: int] { $x * $x }"#))
+        .unwrap()
+        .build()
+        .unwrap();
+    let ctx = ctx.into_shared();
+    let before = clones.load(Ordering::SeqCst);
+
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            let ctx = &ctx;
+            scope.spawn(move || {
+                for i in 0..20i64 {
+                    ctx.eval_raw(format!("let x = {i}; square $x"), PipelineData::empty())
+                        .unwrap();
+                    ctx.eval_raw("[1 2 3] | each {|x| $x * 2 }", PipelineData::empty())
+                        .unwrap();
+                    ctx.eval_raw("def local [] { 1 }; local", PipelineData::empty())
+                        .unwrap();
+                    ctx.call_fn("square", [i]).unwrap();
+                }
+            });
+        }
+    });
+    // only the first evaluation of the script defining `local` may copy the definitions
+    assert!(clones.load(Ordering::SeqCst) - before <= 4);
+}
+
+#[test]
+fn it_only_forks_for_scripts_registering_handlers() {
+    let clones = Arc::new(AtomicUsize::new(0));
+    let ctx: Context = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .with_events()
+        .unwrap()
+        .add_command(CountedCommand(Arc::clone(&clones)))
+        .unwrap()
+        .add_script(String::from(r#"def square [x: int] { $x * $x }"#))
+        .unwrap()
+        .build()
+        .unwrap();
+    let ctx = ctx.into_shared();
+    let before = clones.load(Ordering::SeqCst);
+
+    for i in 0..10i64 {
+        ctx.eval_raw("emit tick 1; square 2", PipelineData::empty())
+            .unwrap();
+        ctx.call_fn("square", [i]).unwrap();
+    }
+    assert_eq!(clones.load(Ordering::SeqCst), before);
+
+    ctx.eval_raw("on tick {|x| $x }", PipelineData::empty())
+        .unwrap();
+    let forked = clones.load(Ordering::SeqCst);
+    assert!(forked > before);
+
+    ctx.eval_raw("do { on tick {|x| $x } }", PipelineData::empty())
+        .unwrap();
+    assert!(clones.load(Ordering::SeqCst) > forked);
+}
+
+fn get_context() -> SharedContext {
+    let ctx: Context = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_script(String::from(r#"def square [x: int] { $x * $x }"#))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    ctx.into_shared()
+}
+
+/// A command that counts how often the definitions of the context are copied
+struct CountedCommand(Arc<AtomicUsize>);
+
+impl Clone for CountedCommand {
+    fn clone(&self) -> Self {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl Command for CountedCommand {
+    fn name(&self) -> &str {
+        "counted"
+    }
+
+    fn description(&self) -> &str {
+        "Does nothing"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::new("counted")
+    }
+
+    fn run(
+        &self,
+        _engine_state: &EngineState,
+        _stack: &mut Stack,
+        _call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        Ok(input)
+    }
+}
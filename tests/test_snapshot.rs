@@ -0,0 +1,90 @@
+use embed_nu::{CommandGroupConfig, Context, PipelineData};
+use nu_protocol::Config;
+
+#[test]
+fn it_forks_contexts_from_snapshots() {
+    let snapshot = get_context().snapshot();
+    let mut first = snapshot.fork().unwrap();
+    let mut second = snapshot.fork().unwrap();
+
+    assert_eq!(eval_string(&mut first, r#"greet "first""#), "Hello first");
+    assert_eq!(
+        eval_string(&mut second, r#"greet "second""#),
+        "Hello second"
+    );
+}
+
+#[test]
+fn it_keeps_fork_changes_out_of_the_template() {
+    let template = get_context();
+    let mut fork = template.fork().unwrap();
+    eval_string(
+        &mut fork,
+        r#"def only-in-fork [] { "fork" }; $env.REQUEST_ID = "42""#,
+    );
+    fork.add_var("request", "fork").unwrap();
+    assert_eq!(eval_string(&mut fork, r#"only-in-fork"#), "fork");
+    assert_eq!(eval_string(&mut fork, r#"$env.REQUEST_ID"#), "42");
+
+    let mut template = template;
+    assert!(!template.has_fn("only-in-fork"));
+    assert!(template.get_var("request").is_none());
+    assert!(
+        template
+            .eval_raw(r#"$env.REQUEST_ID"#, PipelineData::empty())
+            .is_err()
+    );
+    assert!(!template.fork().unwrap().has_fn("only-in-fork"));
+}
+
+#[test]
+fn it_keeps_template_changes_out_of_snapshots() {
+    let mut template = get_context();
+    let snapshot = template.snapshot();
+    eval_string(&mut template, r#"def later [] { "later" }"#);
+
+    assert!(template.has_fn("later"));
+    assert!(!snapshot.fork().unwrap().has_fn("later"));
+}
+
+#[test]
+fn it_isolates_event_handlers_of_forks() {
+    let template = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .with_events()
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut fork = template.fork().unwrap();
+    eval_string(&mut fork, r#"on ping {|x| $x }"#);
+
+    assert_eq!(fork.trigger("ping", 1).unwrap().len(), 1);
+    assert!(
+        template
+            .fork()
+            .unwrap()
+            .trigger("ping", 1)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+fn eval_string(ctx: &mut Context, contents: &str) -> String {
+    ctx.eval_raw(contents, PipelineData::empty())
+        .unwrap()
+        .collect_string("", &Config::default())
+        .unwrap()
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_script(String::from(
+            r#"def greet [name: string] { $"Hello ($name)" }"#,
+        ))
+        .unwrap()
+        .build()
+        .unwrap()
+}
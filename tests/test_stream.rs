@@ -0,0 +1,106 @@
+use std::io::Cursor;
+
+use embed_nu::{CommandContext, CommandGroupConfig, Context, NuCommand, PipelineData};
+use nu_protocol::{Config, ShellError};
+
+/// Returns an endless stream of numbers
+#[derive(NuCommand)]
+#[nu(name = "numbers")]
+struct Numbers {}
+
+impl NuCommand for Numbers {
+    fn run(self, ctx: CommandContext, _input: PipelineData) -> Result<PipelineData, ShellError> {
+        Ok(ctx.stream_iter(0i64..))
+    }
+}
+
+#[test]
+fn it_streams_host_iterators_into_scripts() {
+    let mut ctx = get_context();
+    let input = ctx.stream_iter(1..=100usize);
+    let pipeline = ctx.eval_raw(r#"$in | math sum"#, input).unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(output, "5050");
+}
+
+#[test]
+fn it_streams_host_readers_into_scripts() {
+    let mut ctx = get_context();
+    let input = ctx.stream_reader(Cursor::new(b"one\ntwo\nthree\n".to_vec()));
+    let pipeline = ctx.eval_raw(r#"$in | lines | length"#, input).unwrap();
+    let output = pipeline.collect_string("", &Config::default()).unwrap();
+    assert_eq!(output, "3");
+}
+
+#[test]
+fn it_returns_lazy_streams_from_commands() {
+    let mut ctx = get_context();
+    let pipeline = ctx
+        .eval_raw(r#"numbers | skip 10 | first 3"#, PipelineData::empty())
+        .unwrap();
+    let values = ctx
+        .iter_values(pipeline)
+        .map(|v| v.as_int().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(values, vec![10, 11, 12]);
+}
+
+#[test]
+fn it_consumes_script_output_lazily() {
+    let mut ctx = get_context();
+    let pipeline = ctx
+        .eval_raw(r#"1.. | each {|x| $x * 2 }"#, PipelineData::empty())
+        .unwrap();
+    let values = ctx
+        .iter_values(pipeline)
+        .take(3)
+        .map(|v| v.as_int().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(values, vec![2, 4, 6]);
+}
+
+#[test]
+fn it_iterates_typed_values_lazily() {
+    let mut ctx = get_context();
+    let values = ctx
+        .eval_iter::<i64, _>(r#"1.. | each {|x| $x * 2 }"#, PipelineData::empty())
+        .unwrap()
+        .take(3)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(values, vec![2, 4, 6]);
+}
+
+#[test]
+fn it_returns_stream_errors_as_items() {
+    let mut ctx = get_context();
+    let mut values = ctx
+        .eval_iter::<i64, _>(
+            r#"1..5 | each {|x| if $x == 3 { error make {msg: "boom"} } else { $x } }"#,
+            PipelineData::empty(),
+        )
+        .unwrap();
+    assert_eq!(values.next().unwrap().unwrap(), 1);
+    assert_eq!(values.next().unwrap().unwrap(), 2);
+    assert!(values.next().unwrap().is_err());
+}
+
+#[test]
+fn it_returns_conversion_errors_as_items() {
+    let mut ctx = get_context();
+    let mut values = ctx
+        .eval_iter::<String, _>(r#"["a", {b: 1}]"#, PipelineData::empty())
+        .unwrap();
+    assert_eq!(values.next().unwrap().unwrap(), "a");
+    assert!(values.next().unwrap().is_err());
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .add_nu_command::<Numbers>()
+        .unwrap()
+        .build()
+        .unwrap()
+}
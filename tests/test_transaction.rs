@@ -0,0 +1,75 @@
+use embed_nu::{CommandGroupConfig, Context, PipelineData};
+
+#[test]
+fn it_rolls_back_failed_transactions() {
+    let mut ctx = get_context();
+    let result = ctx.eval_transaction(
+        r#"
+        def broken [] { "defined before the error" }
+        $env.THEME = "dark"
+        error make {msg: "syntax is fine but this fails"}
+        "#,
+        PipelineData::empty(),
+    );
+    assert!(result.is_err());
+    assert!(!ctx.has_fn("broken"));
+    assert!(
+        ctx.eval_raw(r#"$env.THEME"#, PipelineData::empty())
+            .is_err()
+    );
+}
+
+#[test]
+fn it_rolls_back_errors_raised_while_streaming() {
+    let mut ctx = get_context();
+    let result = ctx.eval_transaction(
+        r#"
+        def streamed [] { "defined before the stream" }
+        1..5 | each {|x| if $x == 3 { error make {msg: "boom"} } else { $x } }
+        "#,
+        PipelineData::empty(),
+    );
+    assert!(result.is_err());
+    assert!(!ctx.has_fn("streamed"));
+}
+
+#[test]
+fn it_commits_successful_transactions() {
+    let mut ctx = get_context();
+    let value = ctx
+        .eval_transaction(
+            r#"def greet [] { "hello" }; $env.THEME = "dark"; greet"#,
+            PipelineData::empty(),
+        )
+        .unwrap();
+    assert_eq!(value.as_str().unwrap(), "hello");
+    assert!(ctx.has_fn("greet"));
+    assert_eq!(
+        ctx.eval_transaction(r#"$env.THEME"#, PipelineData::empty())
+            .unwrap()
+            .as_str()
+            .unwrap(),
+        "dark"
+    );
+}
+
+#[test]
+fn it_rolls_back_several_calls_at_once() {
+    let mut ctx = get_context();
+    let result = ctx.transaction(|ctx| {
+        ctx.eval_raw(r#"def stage-one [] { 1 }"#, PipelineData::empty())?;
+        ctx.add_var("second", 2)?;
+        ctx.call_fn("does-not-exist", [] as [String; 0])
+    });
+    assert!(result.is_err());
+    assert!(!ctx.has_fn("stage-one"));
+    assert!(ctx.get_var("second").is_none());
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .build()
+        .unwrap()
+}
@@ -1,3 +1,4 @@
+#![allow(clippy::disallowed_names)]
 use embed_nu::IntoValue;
 use rusty_value::*;
 use std::{mem, path::PathBuf};
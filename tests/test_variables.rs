@@ -0,0 +1,186 @@
+use std::{fs, path::PathBuf};
+
+use embed_nu::{CommandGroupConfig, Context, PipelineData, VarOptions};
+use nu_protocol::{Config, Type};
+
+#[test]
+fn it_adds_mutable_variables() {
+    let mut ctx = get_context();
+    ctx.add_var_with("counter", 0, VarOptions::mutable())
+        .unwrap();
+    assert_eq!(
+        eval_string(&mut ctx, r#"$counter += 1; $counter += 1; $counter"#),
+        "2"
+    );
+}
+
+#[test]
+fn it_rejects_assignments_to_immutable_variables() {
+    let mut ctx = get_context();
+    ctx.add_var("counter", 0).unwrap();
+    assert!(
+        ctx.eval_raw(r#"$counter += 1"#, PipelineData::empty())
+            .is_err()
+    );
+}
+
+#[test]
+fn it_makes_constants_available_to_the_parser() {
+    let dir = TempDir::new("const");
+    fs::write(dir.0.join("lib.nu"), r#"def from-lib [] { "sourced" }"#).unwrap();
+
+    // `source` resolves the path while parsing, which needs a working directory
+    let mut ctx = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .cwd(&dir.0)
+        .unwrap()
+        .build()
+        .unwrap();
+    ctx.add_var_with(
+        "lib_dir",
+        dir.0.to_string_lossy().to_string(),
+        VarOptions::constant(),
+    )
+    .unwrap();
+    assert_eq!(
+        eval_string(
+            &mut ctx,
+            r#"const lib = $"($lib_dir)/lib.nu"; source $lib; from-lib"#
+        ),
+        "sourced"
+    );
+    assert_eq!(
+        eval_string(&mut ctx, r#"$lib_dir"#),
+        dir.0.to_string_lossy()
+    );
+}
+
+#[test]
+fn it_type_checks_variables_at_parse_time() {
+    let mut ctx = get_context();
+    ctx.add_var("name", "world").unwrap();
+    ctx.add_var_with("limit", 10, VarOptions::immutable().with_type(Type::Int))
+        .unwrap();
+    ctx.eval_raw(r#"def takes-int [x: int] { $x }"#, PipelineData::empty())
+        .unwrap();
+
+    assert_eq!(eval_string(&mut ctx, r#"takes-int $limit"#), "10");
+    assert!(
+        ctx.eval_raw(r#"takes-int $name"#, PipelineData::empty())
+            .is_err()
+    );
+}
+
+#[test]
+fn it_updates_mutable_variables_in_place() {
+    let mut ctx = get_context();
+    ctx.add_var_with("config", "old", VarOptions::mutable())
+        .unwrap();
+    ctx.set_var("config", "new").unwrap();
+    assert_eq!(eval_string(&mut ctx, r#"$config"#), "new");
+    assert_eq!(ctx.vars().len(), 1);
+
+    ctx.eval_raw(r#"mut count = 1"#, PipelineData::empty())
+        .unwrap();
+    ctx.set_var("count", 5).unwrap();
+    assert_eq!(eval_string(&mut ctx, r#"$count + 1"#), "6");
+}
+
+#[test]
+fn it_refuses_to_update_immutable_or_unknown_variables() {
+    let mut ctx = get_context();
+    ctx.add_var("fixed", 1).unwrap();
+    assert!(ctx.set_var("fixed", 2).is_err());
+    assert!(ctx.set_var("unknown", 2).is_err());
+    assert_eq!(ctx.get_var("fixed").unwrap().as_int().unwrap(), 1);
+}
+
+#[test]
+fn it_removes_variables() {
+    let mut ctx = get_context();
+    ctx.add_var("secret", "value").unwrap();
+    assert_eq!(ctx.remove_var("secret").unwrap().as_str().unwrap(), "value");
+    assert!(ctx.get_var("secret").is_none());
+    assert!(ctx.remove_var("secret").is_none());
+    assert!(ctx.eval_raw(r#"$secret"#, PipelineData::empty()).is_err());
+}
+
+#[test]
+fn it_lists_visible_variables() {
+    let mut ctx = Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .with_data(String::from("host data"))
+        .add_var("first", 1)
+        .unwrap()
+        .build()
+        .unwrap();
+    ctx.add_var("second", "two").unwrap();
+    ctx.add_var("second", "replaced").unwrap();
+
+    let vars = ctx.vars();
+    assert_eq!(vars.len(), 2);
+    assert_eq!(vars["first"].as_int().unwrap(), 1);
+    assert_eq!(vars["second"].as_str().unwrap(), "replaced");
+}
+
+#[test]
+fn it_updates_variables_used_by_definitions() {
+    let mut ctx = get_context();
+    ctx.add_var("x", 1).unwrap();
+    ctx.eval_raw(r#"def read-x [] { $x }"#, PipelineData::empty())
+        .unwrap();
+    ctx.add_var("x", 2).unwrap();
+
+    assert_eq!(eval_string(&mut ctx, r#"read-x"#), "2");
+    assert_eq!(ctx.vars().len(), 1);
+}
+
+#[test]
+fn it_keeps_variables_used_by_definitions_when_the_type_changes() {
+    let mut ctx = get_context();
+    ctx.add_var("x", 1).unwrap();
+    ctx.eval_raw(r#"def read-x [] { $x }"#, PipelineData::empty())
+        .unwrap();
+    ctx.add_var("x", "str").unwrap();
+
+    assert_eq!(eval_string(&mut ctx, r#"read-x"#), "str");
+    assert_eq!(eval_string(&mut ctx, r#"$x | str length"#), "3");
+
+    ctx.add_var_with("x", 2, VarOptions::mutable()).unwrap();
+    assert_eq!(eval_string(&mut ctx, r#"read-x"#), "str");
+}
+
+fn eval_string(ctx: &mut Context, contents: &str) -> String {
+    ctx.eval_raw(contents, PipelineData::empty())
+        .unwrap()
+        .collect_string("", &Config::default())
+        .unwrap()
+}
+
+/// A directory that is removed on drop
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("embed-nu-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        Self(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn get_context() -> Context {
+    Context::builder()
+        .with_command_groups(CommandGroupConfig::default().all_groups(true))
+        .unwrap()
+        .build()
+        .unwrap()
+}